@@ -0,0 +1,295 @@
+//! A proof checker that is independent of the unifier in [`crate::formula::language`].
+//!
+//! It is deliberately simple rather than fast: formulas are converted to trees, premises
+//! are combined by textbook unification with explicit substitutions, and every claimed
+//! conclusion must be a substitution instance of what the premises yield.
+
+use std::fmt::Display;
+
+use ahash::HashMap;
+
+use crate::{
+    context::{Proof, ProofStep, Source},
+    formula::language::{Language, Normal, Term},
+};
+
+enum Tree<L: Language> {
+    Var(u32),
+    App(L::Variant<()>, Vec<Tree<L>>),
+}
+
+impl<L: Language> Clone for Tree<L> {
+    fn clone(&self) -> Self {
+        match self {
+            Self::Var(x) => Self::Var(*x),
+            Self::App(v, children) => Self::App(v.clone(), children.clone()),
+        }
+    }
+}
+
+impl<L: Language> PartialEq for Tree<L> {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Var(x), Self::Var(y)) => x == y,
+            (Self::App(v, a), Self::App(w, b)) => v == w && a == b,
+            _ => false,
+        }
+    }
+}
+
+type Substitution<L> = HashMap<u32, Tree<L>>;
+
+impl<L: Language> Tree<L> {
+    fn new(f: &Normal<L>) -> Self {
+        let mut terms = f.terms().iter();
+        let tree = Self::read(&mut terms);
+        assert!(terms.next().is_none());
+        tree
+    }
+
+    fn read<'a>(terms: &mut impl Iterator<Item = &'a Term<L, ()>>) -> Self {
+        match terms.next().unwrap() {
+            Term::Var(x) => Self::Var(u32::from(*x)),
+            Term::Term(v) => {
+                let children = L::children(v).iter().map(|()| Self::read(terms)).collect();
+                Self::App(v.clone(), children)
+            }
+        }
+    }
+
+    /// Adds `offset` to every variable.
+    fn shift(&self, offset: u32) -> Self {
+        match self {
+            Self::Var(x) => Self::Var(x + offset),
+            Self::App(v, children) => Self::App(
+                v.clone(),
+                children.iter().map(|c| c.shift(offset)).collect(),
+            ),
+        }
+    }
+
+    fn max_var(&self) -> u32 {
+        match self {
+            Self::Var(x) => *x,
+            Self::App(_, children) => children.iter().map(Self::max_var).max().unwrap_or(0),
+        }
+    }
+
+    /// Replaces the variables bound by `s`, repeatedly.
+    fn apply(&self, s: &Substitution<L>) -> Self {
+        match self {
+            Self::Var(x) => s.get(x).map_or_else(|| self.clone(), |t| t.apply(s)),
+            Self::App(v, children) => {
+                Self::App(v.clone(), children.iter().map(|c| c.apply(s)).collect())
+            }
+        }
+    }
+
+    fn contains(&self, x: u32) -> bool {
+        match self {
+            Self::Var(y) => *y == x,
+            Self::App(_, children) => children.iter().any(|c| c.contains(x)),
+        }
+    }
+}
+
+/// Extends `s` to a most general unifier of `left` and `right`, if there is one.
+fn unify<L: Language>(left: &Tree<L>, right: &Tree<L>, s: &mut Substitution<L>) -> bool {
+    match (left.apply(s), right.apply(s)) {
+        (Tree::Var(x), Tree::Var(y)) if x == y => true,
+        (Tree::Var(x), term) | (term, Tree::Var(x)) => {
+            if term.contains(x) {
+                return false;
+            }
+            s.insert(x, term);
+            true
+        }
+        (Tree::App(v, left), Tree::App(w, right)) => {
+            v == w && left.iter().zip(&right).all(|(l, r)| unify(l, r, s))
+        }
+    }
+}
+
+/// Extends `s` so that it turns `pattern` into `instance`, without changing `instance`.
+fn instantiate<L: Language>(
+    pattern: &Tree<L>,
+    instance: &Tree<L>,
+    s: &mut Substitution<L>,
+) -> bool {
+    match (pattern, instance) {
+        (Tree::Var(x), t) => {
+            if let Some(bound) = s.get(x) {
+                return bound == t;
+            }
+            s.insert(*x, t.clone());
+            true
+        }
+        (Tree::App(v, patterns), Tree::App(w, instances)) => {
+            v == w
+                && patterns
+                    .iter()
+                    .zip(instances)
+                    .all(|(p, i)| instantiate(p, i, s))
+        }
+        (Tree::App(..), Tree::Var(_)) => false,
+    }
+}
+
+fn is_instance<L: Language>(pattern: &Tree<L>, instance: &Tree<L>) -> bool {
+    instantiate(pattern, instance, &mut HashMap::default())
+}
+
+/// What a detachment of `minor` from `major` yields, with the premises' variables kept apart.
+fn detach<L: Language>(minor: &Tree<L>, major: &Tree<L>) -> Option<Tree<L>> {
+    let Tree::App(v, children) = major else {
+        return None;
+    };
+    L::match_implication(v)?;
+    let [antecedent, consequent] = &children[..] else {
+        return None;
+    };
+    let minor = minor.shift(major.max_var() + 1);
+    let mut s = HashMap::default();
+    unify(antecedent, &minor, &mut s).then(|| consequent.apply(&s))
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct CheckError {
+    /// The step that is not justified, counting from 1
+    pub step: usize,
+    pub kind: CheckErrorKind,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum CheckErrorKind {
+    UnknownAxiom,
+    NotAnAxiomInstance,
+    LaterPremise,
+    NotDetachable,
+    NotAConsequence,
+}
+
+impl Display for CheckError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "step {}: ", self.step)?;
+        match self.kind {
+            CheckErrorKind::UnknownAxiom => write!(f, "the axiom does not exist"),
+            CheckErrorKind::NotAnAxiomInstance => write!(f, "not an instance of the axiom"),
+            CheckErrorKind::LaterPremise => write!(f, "a premise is not an earlier step"),
+            CheckErrorKind::NotDetachable => write!(f, "the premises can not be combined"),
+            CheckErrorKind::NotAConsequence => {
+                write!(f, "not an instance of what the premises yield")
+            }
+        }
+    }
+}
+
+/// Checks that every step of `proof` is an instance of an axiom or follows by modus
+/// ponens from earlier steps. The axiom steps refer to `axioms` by their index.
+pub fn check<L: Language>(axioms: &[Normal<L>], proof: &Proof<L>) -> Result<(), CheckError> {
+    let mut steps: Vec<Tree<L>> = Vec::with_capacity(proof.steps.len());
+    for (i, step) in proof.steps.iter().enumerate() {
+        let claim = Tree::new(&step.formula);
+        check_step(axioms, &steps, step, &claim)
+            .map_err(|kind| CheckError { step: i + 1, kind })?;
+        steps.push(claim);
+    }
+    Ok(())
+}
+
+fn check_step<L: Language>(
+    axioms: &[Normal<L>],
+    earlier: &[Tree<L>],
+    step: &ProofStep<L>,
+    claim: &Tree<L>,
+) -> Result<(), CheckErrorKind> {
+    let (pattern, error) = match step.source {
+        Source::Axiom => {
+            let axiom = axioms.get(step.index).ok_or(CheckErrorKind::UnknownAxiom)?;
+            (Tree::new(axiom), CheckErrorKind::NotAnAxiomInstance)
+        }
+        Source::MP(minor, major) => {
+            let premise = |n: usize| {
+                n.checked_sub(1)
+                    .and_then(|n| earlier.get(n))
+                    .ok_or(CheckErrorKind::LaterPremise)
+            };
+            let result =
+                detach(premise(minor)?, premise(major)?).ok_or(CheckErrorKind::NotDetachable)?;
+            (result, CheckErrorKind::NotAConsequence)
+        }
+    };
+    if is_instance(&pattern, claim) {
+        Ok(())
+    } else {
+        Err(error)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        context::{Context, Proof, ProofStep, Source, SourceChoice},
+        formula::langs::ImpNeg,
+    };
+
+    use super::{check, CheckError, CheckErrorKind};
+
+    fn step(formula: &str, index: usize, source: Source) -> ProofStep<ImpNeg> {
+        ProofStep {
+            formula: formula.parse().unwrap(),
+            index,
+            source,
+        }
+    }
+
+    #[test]
+    fn checks_proofs() {
+        let axioms = ImpNeg::lukasiewicz3();
+        let mut context = Context::new(&axioms);
+        context.enable_subsumption();
+        for _ in 0..3 {
+            context.step(&(|_, _, _| ()));
+        }
+        for f in context.entries.keys() {
+            let proof = context.proof_of(f, SourceChoice::Smallest).unwrap();
+            assert_eq!(check(&axioms, &proof), Ok(()));
+        }
+
+        let identity = |last: &str| Proof {
+            steps: vec![
+                step("CCpCqrCCpqCpr", 1, Source::Axiom),
+                step("CpCqp", 0, Source::Axiom),
+                step("CCpqCpp", 2, Source::MP(2, 1)),
+                step(last, 3, Source::MP(2, 3)),
+            ],
+        };
+        assert_eq!(check(&axioms, &identity("Cpp")), Ok(()));
+        // instances of the result are fine as well
+        assert_eq!(check(&axioms, &identity("CNpNp")), Ok(()));
+        let error = |step, kind| Err(CheckError { step, kind });
+        assert_eq!(
+            check(&axioms, &identity("Cpq")),
+            error(4, CheckErrorKind::NotAConsequence)
+        );
+
+        let mut proof = identity("Cpp");
+        proof.steps[1].formula = "CpCpq".parse().unwrap();
+        assert_eq!(
+            check(&axioms, &proof),
+            error(2, CheckErrorKind::NotAnAxiomInstance)
+        );
+        let mut proof = identity("Cpp");
+        proof.steps[2].source = Source::MP(2, 3);
+        assert_eq!(
+            check(&axioms, &proof),
+            error(3, CheckErrorKind::LaterPremise)
+        );
+        proof.steps[0] = step("CCNpNqCqp", 2, Source::Axiom);
+        proof.steps[2].source = Source::MP(2, 1);
+        assert_eq!(
+            check(&axioms, &proof),
+            error(3, CheckErrorKind::NotDetachable)
+        );
+    }
+}
@@ -1,33 +1,250 @@
 use std::{
     fmt::Display,
-    sync::atomic::{AtomicUsize, Ordering},
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc,
+    },
 };
 
-use ahash::HashMap;
+use ahash::{HashMap, HashSet, RandomState};
+use dashmap::DashMap;
 use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
 
-use crate::formula::language::{modus_ponens, Language, Normal};
+mod budget;
+mod checkpoint;
+mod given;
+mod proof;
 
-const MAX_LEN: usize = 64;
+pub use budget::{resident_memory, Budget, Exceeded};
+pub use proof::{Proof, ProofStep, SourceChoice};
+
+use crate::{
+    formula::{
+        index::TermIndex,
+        language::{matching, modus_ponens, Language, Normal, Substitution, Term},
+    },
+    input::Named,
+    weight::Weights,
+};
 
 #[derive(Debug)]
 pub struct Context<L: Language> {
     pub entries: HashMap<Normal<L>, Meta>,
-    next_idx: AtomicUsize,
+    /// The formula of every entry, including retired ones, and the source of its smallest
+    /// known proof by index
+    by_index: HashMap<usize, (Normal<L>, Source)>,
+    /// The index of the next new entry
+    next_idx: usize,
+    names: HashMap<usize, String>,
+    /// Number of completed steps
+    generation: usize,
+    /// The entries added in the last step (or the axioms before the first step)
+    frontier: Vec<(Normal<L>, usize)>,
+    /// The implications of each generation, indexed by their antecedent
+    majors: Vec<TermIndex<L, (Normal<L>, usize)>>,
+    /// All entries indexed by the whole formula, if subsumption is enabled
+    formulas: Option<TermIndex<L, Normal<L>>>,
+    /// Entries that were retired by a more general one but are the source of another entry
+    pub retired: HashMap<Normal<L>, Meta>,
+    /// Indices of the entries that are the source of another entry, if subsumption is enabled
+    used: HashSet<usize>,
+    limits: Limits,
+    weights: Weights<L>,
+    hints: Vec<Hint<L>>,
+    /// The position of each hint in `hints`, indexed by the hint
+    hint_index: TermIndex<L, usize>,
+    /// The queue of the given-clause search, if it is used instead of level saturation
+    given: Option<given::Given<L>>,
+    /// Set from outside to abort the current step, see [`Context::set_interrupt`]
+    interrupt: Option<Arc<AtomicBool>>,
+    budget: Option<budget::Tracker>,
+    counters: Counters,
+}
+
+/// Bounds on the formulas that are added to a [`Context`]. Larger formulas are discarded.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Limits {
+    /// Maximal number of symbols
+    pub len: Option<usize>,
+    /// Maximal nesting depth of connectives
+    pub depth: Option<usize>,
+    /// Maximal number of distinct variables
+    pub vars: Option<usize>,
+    /// Maximal weight, see [`Context::set_weights`]
+    pub weight: Option<usize>,
+}
+
+#[derive(Debug, Default)]
+struct Counters {
+    /// Number of distinct formulas derived in the current step
+    derived: AtomicUsize,
+    attempts: AtomicUsize,
+    avoided: AtomicUsize,
+    subsumed: AtomicUsize,
+    too_long: AtomicUsize,
+    too_deep: AtomicUsize,
+    too_many_vars: AtomicUsize,
+    too_heavy: AtomicUsize,
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+pub struct StepStats {
+    /// Number of attempted applications of modus ponens
+    pub attempts: usize,
+    /// Number of applications that were skipped because the term index ruled them out
+    pub avoided: usize,
+    /// Number of new formulas that were dropped because an entry subsumes them
+    pub subsumed: usize,
+    /// Number of entries that were retired because a new formula subsumes them
+    pub retired: usize,
+    /// Number of new formulas that exceeded [`Limits::len`]
+    pub too_long: usize,
+    /// Number of new formulas that exceeded [`Limits::depth`]
+    pub too_deep: usize,
+    /// Number of new formulas that exceeded [`Limits::vars`]
+    pub too_many_vars: usize,
+    /// Number of new formulas that exceeded [`Limits::weight`]
+    pub too_heavy: usize,
+    /// Whether the step was interrupted, in which case it did not change the context
+    pub interrupted: bool,
+    /// The budget that made the step stop, see [`Context::set_budget`]
+    pub exceeded: Option<Exceeded>,
+}
+
+impl Display for StepStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} unification attempts, {} avoided by the term index",
+            self.attempts, self.avoided
+        )?;
+        if self.subsumed > 0 || self.retired > 0 {
+            write!(f, ", {} subsumed, {} retired", self.subsumed, self.retired)?;
+        }
+        if self.too_long > 0 || self.too_deep > 0 || self.too_many_vars > 0 || self.too_heavy > 0 {
+            write!(
+                f,
+                ", rejected {} too long, {} too deep, {} with too many variables, {} too heavy",
+                self.too_long, self.too_deep, self.too_many_vars, self.too_heavy
+            )?;
+        }
+        if let Some(exceeded) = self.exceeded {
+            write!(f, ", stopped by the {exceeded} budget")?;
+        } else if self.interrupted {
+            write!(f, ", interrupted")?;
+        }
+        Ok(())
+    }
+}
+
+/// Number of minor premises a step processes at once. The interrupt and the budget
+/// are checked between chunks as well as within them.
+const STEP_CHUNK: usize = 4096;
+
+/// A minor premise of a step with its index and the majors it is combined with
+type Minor<'a, L> = (&'a Normal<L>, usize, &'a [TermIndex<L, (Normal<L>, usize)>]);
+
+/// The sources a formula was derived from in the current step. Most formulas are
+/// derived once, so the others are kept apart.
+#[derive(Debug)]
+struct Derivations {
+    /// The smallest source
+    first: Source,
+    more: Vec<Source>,
+}
+
+impl Derivations {
+    fn add(&mut self, mut source: Source) {
+        if source == self.first || self.more.contains(&source) {
+            return;
+        }
+        if source < self.first {
+            std::mem::swap(&mut self.first, &mut source);
+        }
+        self.more.push(source);
+    }
 }
 
-#[derive(PartialEq, Eq, Hash, Debug, Clone)]
+/// The distinct formulas derived in the current step, in a map that is shared between
+/// threads. A formula that is derived again only adds its source.
+struct NewEntries<L: Language>(DashMap<Normal<L>, Derivations, RandomState>);
+
+impl<L: Language> NewEntries<L> {
+    fn new() -> Self {
+        Self(DashMap::default())
+    }
+
+    /// Adds a derivation of `f`, returns whether `f` was not derived before.
+    fn add(&self, f: Normal<L>, source: Source) -> bool {
+        match self.0.entry(f) {
+            dashmap::Entry::Occupied(mut e) => {
+                e.get_mut().add(source);
+                false
+            }
+            dashmap::Entry::Vacant(e) => {
+                e.insert(Derivations {
+                    first: source,
+                    more: Vec::new(),
+                });
+                true
+            }
+        }
+    }
+
+    /// The formulas with their sources in ascending order, ordered by their smallest
+    /// source, so that the order does not depend on the scheduling of the threads.
+    fn into_sorted(self) -> Vec<(Normal<L>, Derivations)> {
+        let mut entries: Vec<_> = self.0.into_iter().collect();
+        entries.sort_unstable_by_key(|(_, derivations)| derivations.first);
+        for (_, derivations) in &mut entries {
+            derivations.more.sort_unstable();
+        }
+        entries
+    }
+}
+
+/// A formula known to be useful, typically a step of a proof in another axiom system.
+#[derive(Debug, Clone)]
+pub struct Hint<L: Language> {
+    pub name: Option<String>,
+    pub formula: Normal<L>,
+    /// The step in which an entry matching the hint was first added, 0 for axioms
+    pub matched: Option<usize>,
+}
+
+#[derive(PartialEq, Eq, Hash, Debug, Clone, Serialize, Deserialize)]
 pub struct Meta {
     pub index: usize,
     pub sources: Vec<Source>,
+    /// The step in which the entry was added, 0 for axioms
+    pub generation: usize,
+    /// Number of distinct modus ponens steps in the smallest known proof
+    pub size: usize,
+    /// Depth of the shallowest known proof tree, 0 for axioms
+    pub depth: usize,
+    /// The position in `sources` of the source of the smallest known proof
+    pub best: usize,
 }
 
-#[derive(PartialEq, Eq, Hash, Debug, Clone, Copy)]
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Clone, Copy, Serialize, Deserialize)]
 pub enum Source {
     Axiom,
     MP(usize, usize),
 }
 
+impl Source {
+    /// The indices of the entries this one was derived from.
+    pub fn premises(&self) -> impl Iterator<Item = usize> {
+        match *self {
+            Source::Axiom => None,
+            Source::MP(a, b) => Some([a, b]),
+        }
+        .into_iter()
+        .flatten()
+    }
+}
+
 impl Display for Source {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -38,69 +255,750 @@ impl Display for Source {
 }
 
 impl<L: Language> Context<L> {
+    /// Creates a context containing only the axioms. Of several equal axioms, only the
+    /// first becomes an entry, but every axiom keeps its index.
     pub fn new(axioms: &[Normal<L>]) -> Self {
-        let entries = axioms
+        let mut entries = HashMap::default();
+        for (index, f) in axioms.iter().enumerate() {
+            entries.entry(f.clone()).or_insert(Meta {
+                index,
+                sources: vec![Source::Axiom],
+                generation: 0,
+                size: 0,
+                depth: 0,
+                best: 0,
+            });
+        }
+
+        let frontier: Vec<_> = entries.iter().map(|(f, m)| (f.clone(), m.index)).collect();
+        Self {
+            by_index: frontier
+                .iter()
+                .map(|(f, i)| (*i, (f.clone(), Source::Axiom)))
+                .collect(),
+            entries,
+            next_idx: axioms.len(),
+            names: HashMap::default(),
+            generation: 0,
+            majors: vec![index_majors(&frontier)],
+            frontier,
+            formulas: None,
+            retired: HashMap::default(),
+            used: HashSet::default(),
+            limits: Limits::default(),
+            weights: Weights::default(),
+            hints: Vec::new(),
+            hint_index: TermIndex::default(),
+            given: None,
+            interrupt: None,
+            budget: None,
+            counters: Counters::default(),
+        }
+    }
+
+    /// Like [`Context::new`], but axioms that have a name are labelled with it instead
+    /// of their index.
+    pub fn with_names(axioms: &[Named<L>]) -> Self {
+        let formulas: Vec<_> = axioms.iter().map(|(_, f)| f.clone()).collect();
+        let mut context = Self::new(&formulas);
+        context.names = axioms
             .iter()
             .enumerate()
-            .map(|(index, f)| {
-                (
-                    f.clone(),
-                    Meta {
-                        index,
-                        sources: vec![Source::Axiom],
-                    },
-                )
+            .filter_map(|(index, (name, _))| Some((index, name.clone()?)))
+            .collect();
+        context
+    }
+
+    /// Makes [`Context::step`] and [`Context::given_step`] stop early and discard their
+    /// results once `flag` is set.
+    /// The flag is not reset.
+    pub fn set_interrupt(&mut self, flag: Arc<AtomicBool>) {
+        self.interrupt = Some(flag);
+    }
+
+    fn is_interrupted(&self) -> bool {
+        self.is_over_budget()
+            || self
+                .interrupt
+                .as_ref()
+                .is_some_and(|flag| flag.load(Ordering::Relaxed))
+    }
+
+    /// Whether the current step should stop before trying the next minor premise.
+    fn should_stop(&self) -> bool {
+        self.check_budget(true);
+        self.is_interrupted()
+    }
+
+    /// Number of formulas waiting to be picked by the given-clause search.
+    pub fn pending(&self) -> Option<usize> {
+        self.given.as_ref().map(given::Given::pending)
+    }
+
+    pub fn set_limits(&mut self, limits: Limits) {
+        self.limits = limits;
+    }
+
+    /// Sets the weights used to rank formulas in the given-clause search and for
+    /// [`Limits::weight`]. By default, the weight of a formula is its length.
+    pub fn set_weights(&mut self, weights: Weights<L>) {
+        self.weights = weights;
+    }
+
+    /// Sets the hints. A formula that subsumes a hint gets the weight [`Weights::hint`]
+    /// instead of its usual weight, and the first entry that does marks the hint as matched.
+    pub fn set_hints(&mut self, hints: Vec<Named<L>>) {
+        self.hint_index = TermIndex::default();
+        self.hints = hints
+            .into_iter()
+            .enumerate()
+            .map(|(i, (name, formula))| {
+                self.hint_index.insert(formula.terms(), i);
+                Hint {
+                    name,
+                    formula,
+                    matched: None,
+                }
             })
-            .collect::<HashMap<_, _>>();
+            .collect();
+        let entries: Vec<_> = self
+            .entries
+            .iter()
+            .map(|(f, m)| (f.clone(), m.generation))
+            .collect();
+        for (f, generation) in entries {
+            self.match_hints(&f, generation);
+        }
+    }
 
-        let next_idx = entries.len();
-        Self {
-            entries,
-            next_idx: AtomicUsize::new(next_idx),
+    pub fn hints(&self) -> &[Hint<L>] {
+        &self.hints
+    }
+
+    /// Number of completed steps, or of picks in the given-clause search.
+    pub fn generation(&self) -> usize {
+        self.generation
+    }
+
+    /// The positions of the hints that are subsumed by `f`.
+    fn hints_subsumed_by(&self, f: &Normal<L>) -> Vec<usize> {
+        let mut matches = Vec::new();
+        self.hint_index.instances(f.terms(), &mut |&i| {
+            if f.subsumes(&self.hints[i].formula) {
+                matches.push(i);
+            }
+        });
+        matches
+    }
+
+    fn match_hints(&mut self, f: &Normal<L>, generation: usize) {
+        for i in self.hints_subsumed_by(f) {
+            self.hints[i].matched.get_or_insert(generation);
         }
     }
 
-    pub fn new_entries_iter<'a, F: Fn(&Normal<L>, &Normal<L>, &Normal<L>) + Send + Sync + 'a>(
-        &'a mut self,
+    /// The weight of `f`, taking hints into account.
+    pub fn weight(&self, f: &Normal<L>) -> usize {
+        if self.hints_subsumed_by(f).is_empty() {
+            self.weights.weight(f)
+        } else {
+            self.weights.hint
+        }
+    }
+
+    /// Whether `f` is within the limits, counting the limit that is exceeded otherwise.
+    fn within_limits(&self, f: &Normal<L>) -> bool {
+        let exceeds = |limit: Option<usize>, value: usize| limit.is_some_and(|l| value > l);
+        let counter = if exceeds(self.limits.len, f.len()) {
+            &self.counters.too_long
+        } else if exceeds(self.limits.depth, f.depth()) {
+            &self.counters.too_deep
+        } else if exceeds(self.limits.vars, f.num_vars()) {
+            &self.counters.too_many_vars
+        } else if self.limits.weight.is_some() && exceeds(self.limits.weight, self.weight(f)) {
+            &self.counters.too_heavy
+        } else {
+            return true;
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+        false
+    }
+
+    /// Drops new formulas that are substitution instances of an entry and retires entries
+    /// that are substitution instances of a new formula. Retired entries are no longer
+    /// used for modus ponens, but are kept in [`Context::retired`] if another entry
+    /// depends on them.
+    pub fn enable_subsumption(&mut self) {
+        let mut formulas = TermIndex::default();
+        for (f, meta) in &self.entries {
+            formulas.insert(f.terms(), f.clone());
+            for source in &meta.sources {
+                self.used.extend(source.premises());
+            }
+        }
+        self.formulas = Some(formulas);
+    }
+
+    /// Whether an entry subsumes `f`.
+    fn is_subsumed(&self, f: &Normal<L>) -> bool {
+        let Some(formulas) = &self.formulas else {
+            return false;
+        };
+        let mut subsumed = false;
+        formulas.generalizations(f.terms(), &mut |g| subsumed |= g.subsumes(f));
+        subsumed
+    }
+
+    fn retire(&mut self, f: &Normal<L>) {
+        let meta = self.entries.remove(f).unwrap();
+        if let Some(formulas) = &mut self.formulas {
+            formulas.remove(f.terms(), |g| g == f);
+        }
+        if let (Some(antecedent), Some(majors)) =
+            (f.antecedent(), self.majors.get_mut(meta.generation))
+        {
+            majors.remove(antecedent, |(_, i)| *i == meta.index);
+        }
+        if self.used.contains(&meta.index) {
+            self.retired.insert(f.clone(), meta);
+        } else {
+            self.by_index.remove(&meta.index);
+        }
+    }
+
+    /// Retires the entries that are subsumed by an entry of the frontier.
+    fn retire_subsumed(&mut self) -> usize {
+        let mut retired = 0;
+        let frontier = std::mem::take(&mut self.frontier);
+        for (f, _) in &frontier {
+            let Some(formulas) = &self.formulas else {
+                break;
+            };
+            if !self.entries.contains_key(f) {
+                continue;
+            }
+            let mut instances = Vec::new();
+            formulas.instances(f.terms(), &mut |g| {
+                if g != f && f.subsumes(g) {
+                    instances.push(g.clone());
+                }
+            });
+            for g in &instances {
+                self.retire(g);
+            }
+            retired += instances.len();
+        }
+        self.frontier = frontier;
+        self.frontier.retain(|(f, _)| self.entries.contains_key(f));
+        retired
+    }
+
+    /// The entry with the lowest index that subsumes `target`, together with the
+    /// substitution that turns it into `target`.
+    pub fn find_subsuming<'a>(
+        &'a self,
+        target: &'a Normal<L>,
+    ) -> Option<(&'a Normal<L>, &'a Meta, Substitution<'a, L>)> {
+        let subsuming = |f: &'a Normal<L>| {
+            let substitution = matching(f.terms(), target.terms())?;
+            Some((f, &self.entries[f], substitution))
+        };
+        if let Some(formulas) = &self.formulas {
+            let mut found: Option<(&Normal<L>, &Meta, _)> = None;
+            formulas.generalizations(target.terms(), &mut |f| {
+                if let Some(candidate) = subsuming(f) {
+                    if found
+                        .as_ref()
+                        .is_none_or(|(_, m, _)| candidate.1.index < m.index)
+                    {
+                        found = Some(candidate);
+                    }
+                }
+            });
+            found
+        } else {
+            self.entries
+                .par_iter()
+                .filter_map(|(f, _)| subsuming(f))
+                .min_by_key(|(_, m, _)| m.index)
+        }
+    }
+
+    /// The name of the axiom with the given index, or its number counting from 1 as in
+    /// D-notation if it has no name.
+    pub fn axiom_label(&self, index: usize) -> String {
+        self.names
+            .get(&index)
+            .cloned()
+            .unwrap_or_else(|| (index + 1).to_string())
+    }
+
+    /// The label of the entry with the given index: the name of a named axiom, `axiom`
+    /// and the number of an unnamed one, otherwise the index.
+    pub fn label(&self, index: usize) -> String {
+        match self.by_index.get(&index) {
+            Some((_, Source::Axiom)) if self.names.contains_key(&index) => self.axiom_label(index),
+            Some((_, Source::Axiom)) => format!("axiom {}", index + 1),
+            _ => index.to_string(),
+        }
+    }
+
+    fn try_modus_ponens<F: Fn(&Normal<L>, &Normal<L>, &Normal<L>)>(
+        &self,
+        (f1, i1): (&Normal<L>, usize),
+        (f2, i2): (&Normal<L>, usize),
+        for_each_new: &F,
+    ) -> Option<(Normal<L>, Source)> {
+        modus_ponens(f1, f2)
+            .filter(|f| self.within_limits(f) && !self.entries.contains_key(f))
+            .filter(|f| {
+                let subsumed = self.is_subsumed(f);
+                if subsumed {
+                    self.counters.subsumed.fetch_add(1, Ordering::Relaxed);
+                }
+                !subsumed
+            })
+            .inspect(|f| for_each_new(f1, f2, f))
+            .map(|res| (res, Source::MP(i1, i2)))
+    }
+
+    /// The entries in `indices` whose key may unify with `query`.
+    fn partners<'a>(
+        &self,
+        query: &[Term<L, ()>],
+        indices: &'a [TermIndex<L, (Normal<L>, usize)>],
+    ) -> Vec<&'a (Normal<L>, usize)> {
+        let mut partners = Vec::new();
+        for index in indices {
+            index.unifiable(query, &mut |entry| partners.push(entry));
+        }
+        let total = indices.iter().map(TermIndex::len).sum::<usize>();
+        self.counters
+            .attempts
+            .fetch_add(partners.len(), Ordering::Relaxed);
+        self.counters
+            .avoided
+            .fetch_add(total - partners.len(), Ordering::Relaxed);
+        partners
+    }
+
+    /// The minor premises of the next step: the entries added in the last step with every
+    /// major, and the older entries with the majors added in the last step. All other pairs
+    /// have already been tried in an earlier step.
+    fn minors(&self) -> Vec<Minor<'_, L>> {
+        let new_majors = &self.majors[self.generation..];
+        let new_minors = self.frontier.iter().map(|(f, i)| (f, *i, &self.majors[..]));
+        let old_minors = self
+            .entries
+            .iter()
+            .filter(|(_, m)| m.generation < self.generation)
+            .map(|(f, m)| (f, m.index, new_majors));
+        new_minors.chain(old_minors).collect()
+    }
+
+    /// Applies modus ponens to every minor premise in `minors` and its majors.
+    fn derive<'a, F: Fn(&Normal<L>, &Normal<L>, &Normal<L>) + Send + Sync + 'a>(
+        &'a self,
+        minors: impl ParallelIterator<Item = Minor<'a, L>> + 'a,
         for_each_new: &'a F,
-    ) -> impl ParallelIterator<Item = (Normal<L>, Source, usize)> + 'a {
-        self.entries.par_iter().flat_map_iter(|(f1, m1)| {
-            self.entries.iter().filter_map(|(f2, m2)| {
-                modus_ponens(f1, f2)
-                    .filter(|f|
-                        // f.len() < MAX_LEN &&
-                        !self.entries.contains_key(f))
-                    .inspect(|f| for_each_new(f1, f2, f))
-                    .map(|res| {
-                        (
-                            res,
-                            Source::MP(m1.index, m2.index),
-                            self.next_idx.fetch_add(1, Ordering::Relaxed),
-                        )
+    ) -> impl ParallelIterator<Item = (Normal<L>, Source)> + 'a {
+        minors
+            .filter(|_| !self.should_stop())
+            .flat_map_iter(move |(f1, i1, majors)| {
+                self.partners(f1.terms(), majors)
+                    .into_iter()
+                    .take_while(|_| !self.is_interrupted())
+                    .filter_map(move |(f2, i2)| {
+                        self.try_modus_ponens((f1, i1), (f2, *i2), for_each_new)
                     })
             })
-        })
+    }
+
+    /// Applies modus ponens to every pair of entries where at least one of them was added
+    /// in the last step. All other pairs have already been tried in an earlier step.
+    ///
+    /// # Panics
+    /// If the given-clause search is enabled, which has no steps of this kind.
+    pub fn new_entries_iter<'a, F: Fn(&Normal<L>, &Normal<L>, &Normal<L>) + Send + Sync + 'a>(
+        &'a self,
+        for_each_new: &'a F,
+    ) -> impl ParallelIterator<Item = (Normal<L>, Source)> + 'a {
+        assert!(
+            self.given.is_none(),
+            "the given-clause search has no level saturation steps"
+        );
+        self.derive(self.minors().into_par_iter(), for_each_new)
     }
 
     pub fn step<F: Fn(&Normal<L>, &Normal<L>, &Normal<L>) + Send + Sync>(
         &mut self,
         for_each_new: &F,
-    ) {
-        let new_entries = self.new_entries_iter(for_each_new).collect_vec_list();
-
-        new_entries
-            .into_iter()
-            .flatten()
-            .for_each(|(f, source, index)| {
-                let entry = self.entries.entry(f);
-                let meta = entry.or_insert(Meta {
-                    index,
-                    sources: Vec::new(),
+    ) -> StepStats {
+        // every formula is kept once, as soon as it is derived
+        let new_entries = NewEntries::new();
+        for chunk in self.minors().chunks(STEP_CHUNK) {
+            if self.should_stop() {
+                break;
+            }
+            self.derive(chunk.par_iter().copied(), for_each_new)
+                .for_each(|(f, source)| {
+                    if new_entries.add(f, source) {
+                        self.counters.derived.fetch_add(1, Ordering::Relaxed);
+                    }
                 });
-                if !meta.sources.contains(&source) {
-                    meta.sources.push(source);
+        }
+        if self.is_interrupted() {
+            let mut stats = self.take_stats(0);
+            stats.interrupted = true;
+            stats.exceeded = self.exceeded_budget();
+            return stats;
+        }
+
+        self.generation += 1;
+        self.frontier.clear();
+
+        for (f, derivations) in new_entries.into_sorted() {
+            // a formula that is not added by its first source is subsumed
+            let Some((f, index)) = self.insert(f, derivations.first) else {
+                continue;
+            };
+            for source in derivations.more {
+                self.insert(f.clone(), source);
+            }
+            self.frontier.push((f, index));
+        }
+        let retired = self.retire_subsumed();
+        self.majors.push(index_majors(&self.frontier));
+
+        self.take_stats(retired)
+    }
+
+    /// The entry with the given index, also if it was retired.
+    fn entry(&self, index: usize) -> Option<(&Normal<L>, &Meta)> {
+        let (f, _) = self.by_index.get(&index)?;
+        let meta = self.entries.get(f).or_else(|| self.retired.get(f))?;
+        Some((f, meta))
+    }
+
+    /// The size and depth of the proof that uses `source` and the smallest known proofs
+    /// of its premises. Sizes are not updated when a premise later gets a smaller proof.
+    ///
+    /// If the size can not be smaller than `limit`, a lower bound is returned instead.
+    fn measure(&self, source: Source, limit: usize) -> (usize, usize) {
+        let Source::MP(a, b) = source else {
+            return (0, 0);
+        };
+        let (_, meta_a) = self.entry(a).unwrap();
+        let (_, meta_b) = self.entry(b).unwrap();
+        let depth = 1 + meta_a.depth.max(meta_b.depth);
+        let lower_bound = 1 + meta_a.size.max(meta_b.size);
+        if lower_bound >= limit {
+            return (lower_bound, depth);
+        }
+        let mut seen = HashSet::default();
+        let mut stack = vec![a, b];
+        let mut size = 1;
+        while let Some(i) = stack.pop() {
+            if !seen.insert(i) {
+                continue;
+            }
+            if let Source::MP(a, b) = self.by_index[&i].1 {
+                size += 1;
+                stack.extend([a, b]);
+            }
+        }
+        (size, depth)
+    }
+
+    /// Adds a new formula produced in this generation, or the source if the formula is
+    /// already known. Returns the formula and its index if it became a new entry; only
+    /// new entries take an index, so the indices have no gaps.
+    fn insert(&mut self, f: Normal<L>, source: Source) -> Option<(Normal<L>, usize)> {
+        if self.formulas.is_some() {
+            self.used.extend(source.premises());
+        }
+        if let Some(meta) = self.entries.get(&f) {
+            if !meta.sources.contains(&source) {
+                let (size, depth) = self.measure(source, meta.size);
+                let meta = self.entries.get_mut(&f).unwrap();
+                meta.sources.push(source);
+                if size < meta.size {
+                    meta.size = size;
+                    meta.best = meta.sources.len() - 1;
+                    self.by_index.get_mut(&meta.index).unwrap().1 = source;
                 }
-            });
-        // println!("max len: {}", max_len.load(Ordering::Relaxed));
+                meta.depth = meta.depth.min(depth);
+            }
+            return None;
+        }
+        if self.is_subsumed(&f) {
+            self.counters.subsumed.fetch_add(1, Ordering::Relaxed);
+            return None;
+        }
+        if let Some(formulas) = &mut self.formulas {
+            formulas.insert(f.terms(), f.clone());
+        }
+        self.match_hints(&f, self.generation);
+        let index = self.next_idx;
+        self.next_idx += 1;
+        self.by_index.insert(index, (f.clone(), source));
+        let (size, depth) = self.measure(source, usize::MAX);
+        self.entries.insert(
+            f.clone(),
+            Meta {
+                index,
+                sources: vec![source],
+                generation: self.generation,
+                size,
+                depth,
+                best: 0,
+            },
+        );
+        Some((f, index))
+    }
+
+    /// The statistics collected since the last call.
+    fn take_stats(&mut self, retired: usize) -> StepStats {
+        let counters = std::mem::take(&mut self.counters);
+        StepStats {
+            attempts: counters.attempts.into_inner(),
+            avoided: counters.avoided.into_inner(),
+            subsumed: counters.subsumed.into_inner(),
+            retired,
+            too_long: counters.too_long.into_inner(),
+            too_deep: counters.too_deep.into_inner(),
+            too_many_vars: counters.too_many_vars.into_inner(),
+            too_heavy: counters.too_heavy.into_inner(),
+            interrupted: false,
+            exceeded: None,
+        }
+    }
+}
+
+fn index_majors<L: Language>(formulas: &[(Normal<L>, usize)]) -> TermIndex<L, (Normal<L>, usize)> {
+    let mut index = TermIndex::default();
+    for (f, i) in formulas {
+        if let Some(antecedent) = f.antecedent() {
+            index.insert(antecedent, (f.clone(), *i));
+        }
+    }
+    index
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    };
+
+    use ahash::HashSet;
+
+    use crate::formula::{
+        langs::ImpNeg,
+        language::{modus_ponens, Language, Normal},
+    };
+
+    use super::{Context, Limits, NewEntries, Source, SourceChoice};
+
+    /// Level saturation that tries every pair in every step.
+    fn naive<L: Language>(axioms: Vec<Normal<L>>, steps: usize) -> HashSet<Normal<L>> {
+        let mut entries: HashSet<_> = axioms.into_iter().collect();
+        for _ in 0..steps {
+            let new: Vec<_> = entries
+                .iter()
+                .flat_map(|p| entries.iter().filter_map(|f| modus_ponens(p, f)))
+                .collect();
+            entries.extend(new);
+        }
+        entries
+    }
+
+    #[test]
+    fn semi_naive_matches_naive() {
+        for (axioms, steps) in [(ImpNeg::meredith(), 4), (ImpNeg::lukasiewicz3(), 2)] {
+            let mut context = Context::new(&axioms);
+            for _ in 0..steps {
+                context.step(&(|_, _, _| ()));
+            }
+            let entries: HashSet<_> = context.entries.into_keys().collect();
+            assert_eq!(entries, naive(axioms, steps));
+        }
+    }
+
+    #[test]
+    fn subsumption_keeps_most_general() {
+        let axioms = ImpNeg::lukasiewicz3();
+        let mut context = Context::new(&axioms);
+        context.enable_subsumption();
+        for _ in 0..2 {
+            context.step(&(|_, _, _| ()));
+        }
+        let entries: Vec<_> = context.entries.keys().collect();
+        for f in &entries {
+            for g in &entries {
+                assert!(f == g || !f.subsumes(g), "{f:?} subsumes {g:?}");
+            }
+        }
+        for f in naive(axioms, 2) {
+            assert!(entries.iter().any(|g| g.subsumes(&f)), "{f:?} not subsumed");
+        }
+        for meta in context.entries.values().chain(context.retired.values()) {
+            for i in meta.sources.iter().flat_map(super::Source::premises) {
+                assert!(context
+                    .entries
+                    .values()
+                    .chain(context.retired.values())
+                    .any(|m| m.index == i));
+            }
+        }
+    }
+
+    #[test]
+    fn interrupted_steps_have_no_effect() {
+        let mut context = Context::new(&ImpNeg::lukasiewicz3());
+        let flag = Arc::new(AtomicBool::new(false));
+        context.set_interrupt(flag.clone());
+        context.step(&(|_, _, _| ()));
+        let entries = context.entries.clone();
+
+        flag.store(true, Ordering::Relaxed);
+        let stats = context.step(&(|_, _, _| ()));
+        assert!(stats.interrupted);
+        assert_eq!(context.generation(), 1);
+        assert_eq!(context.entries, entries);
+
+        flag.store(false, Ordering::Relaxed);
+        assert!(!context.step(&(|_, _, _| ())).interrupted);
+        assert_eq!(context.generation(), 2);
+        assert_eq!(context.entries.len(), 36);
+    }
+
+    #[test]
+    fn duplicates_only_add_sources() {
+        let new_entries = NewEntries::<ImpNeg>::new();
+        let f: Normal<ImpNeg> = "CpCqp".parse().unwrap();
+        assert!(new_entries.add(f.clone(), Source::MP(1, 0)));
+        assert!(!new_entries.add(f.clone(), Source::MP(0, 2)));
+        assert!(!new_entries.add(f.clone(), Source::MP(1, 0)));
+        assert_eq!(new_entries.0.len(), 1);
+        let [(g, derivations)] = &new_entries.into_sorted()[..] else {
+            panic!("a formula was added twice");
+        };
+        assert_eq!(g, &f);
+        assert_eq!(derivations.first, Source::MP(0, 2));
+        assert_eq!(derivations.more, [Source::MP(1, 0)]);
+    }
+
+    #[test]
+    fn indices_are_dense() {
+        let axioms = ImpNeg::lukasiewicz3();
+        let mut context = Context::new(&axioms);
+        for _ in 0..3 {
+            context.step(&(|_, _, _| ()));
+        }
+        let mut indices: Vec<_> = context.by_index.keys().copied().collect();
+        indices.sort_unstable();
+        assert_eq!(indices, (0..context.next_idx).collect::<Vec<_>>());
+
+        // the indices do not depend on the order in which formulas are derived
+        let threads = rayon::ThreadPoolBuilder::new()
+            .num_threads(4)
+            .build()
+            .unwrap();
+        let mut again = Context::new(&axioms);
+        threads.install(|| {
+            for _ in 0..3 {
+                again.step(&(|_, _, _| ()));
+            }
+        });
+        assert_eq!(again.entries, context.entries);
+    }
+
+    #[test]
+    fn duplicate_axioms() {
+        let mut axioms = ImpNeg::lukasiewicz3();
+        // equal to the first axiom after renaming the variables
+        axioms.insert(1, "CqCpq".parse().unwrap());
+        let mut context = Context::new(&axioms);
+        assert_eq!(context.entries.len(), 3);
+        for _ in 0..2 {
+            context.step(&(|_, _, _| ()));
+        }
+        for (f, meta) in &context.entries {
+            assert!(meta.generation == 0 || meta.index >= axioms.len());
+            let proof = context.proof_of(f, SourceChoice::Smallest).unwrap();
+            assert!(crate::check::check(&axioms, &proof).is_ok());
+            // unnamed axioms are numbered from 1 as in D-notation
+            let label = if meta.generation == 0 {
+                assert_eq!(
+                    context.axiom_label(meta.index),
+                    (meta.index + 1).to_string()
+                );
+                format!("axiom {}", meta.index + 1)
+            } else {
+                meta.index.to_string()
+            };
+            assert_eq!(context.label(meta.index), label);
+        }
+    }
+
+    #[test]
+    fn limits() {
+        let mut context = Context::new(&ImpNeg::lukasiewicz3());
+        context.set_limits(Limits {
+            len: Some(12),
+            depth: Some(4),
+            vars: Some(2),
+            weight: None,
+        });
+        let mut stats = Vec::new();
+        for _ in 0..3 {
+            stats.push(context.step(&(|_, _, _| ())));
+        }
+        for (f, meta) in &context.entries {
+            if meta.generation > 0 {
+                assert!(f.len() <= 12 && f.depth() <= 4 && f.num_vars() <= 2);
+            }
+        }
+        assert!(stats.iter().any(|s| s.too_long > 0));
+        assert!(stats.iter().any(|s| s.too_many_vars > 0));
+    }
+
+    #[test]
+    fn finds_subsuming() {
+        let target = "CNpCqNp".parse().unwrap();
+        let mut context = Context::new(&ImpNeg::lukasiewicz3());
+        for subsumption in [false, true] {
+            if subsumption {
+                context.enable_subsumption();
+            }
+            let (f, meta, substitution) = context.find_subsuming(&target).unwrap();
+            assert_eq!(f, &ImpNeg::lukasiewicz3()[0]);
+            assert_eq!(meta.index, 0);
+            assert_eq!(substitution.len(), 2);
+        }
+        assert!(context.find_subsuming(&"Cpp".parse().unwrap()).is_none());
+    }
+
+    #[test]
+    fn hints() {
+        let axioms = ImpNeg::lukasiewicz3();
+        let derived: Vec<_> = naive(axioms.clone(), 1)
+            .into_iter()
+            .filter(|f| !axioms.contains(f))
+            .collect();
+        let hint = derived.iter().min_by_key(|f| f.len()).unwrap().clone();
+
+        // an instance of the first axiom
+        let instance = "CNpCqNp".parse().unwrap();
+
+        let mut context = Context::new(&axioms);
+        context.set_hints(vec![(Some("h".to_owned()), hint.clone()), (None, instance)]);
+        assert_eq!(context.weight(&hint), 0);
+        assert!(derived.iter().any(|f| context.weight(f) == f.len()));
+        context.step(&(|_, _, _| ()));
+        let matched: Vec<_> = context.hints().iter().map(|h| h.matched).collect();
+        assert_eq!(matched, [Some(1), Some(0)]);
     }
 }
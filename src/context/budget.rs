@@ -0,0 +1,174 @@
+//! Bounds on the time, entries and memory a search may use before it stops.
+
+use std::{
+    fmt::Display,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        OnceLock,
+    },
+    time::{Duration, Instant},
+};
+
+use crate::formula::language::Language;
+
+use super::Context;
+
+/// How many checks during a step go by between two measurements of the memory
+const MEMORY_CHECK_INTERVAL: usize = 64;
+
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Budget {
+    /// Time since [`Context::set_budget`]
+    pub time: Option<Duration>,
+    /// Maximal number of entries
+    pub entries: Option<usize>,
+    /// Maximal resident memory of the process in bytes, see [`resident_memory`]
+    pub memory: Option<usize>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Exceeded {
+    Time,
+    Entries,
+    Memory,
+}
+
+impl Display for Exceeded {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Time => write!(f, "time"),
+            Self::Entries => write!(f, "entry"),
+            Self::Memory => write!(f, "memory"),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub(super) struct Tracker {
+    budget: Budget,
+    start: Instant,
+    checks: AtomicUsize,
+    /// The budget that was exceeded first; once set, every step is aborted
+    exceeded: OnceLock<Exceeded>,
+}
+
+/// The resident memory of the process in bytes, `None` if the platform does not report it.
+pub fn resident_memory() -> Option<usize> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    let kb: usize = status
+        .lines()
+        .find_map(|line| line.strip_prefix("VmRSS:"))?
+        .trim()
+        .strip_suffix("kB")?
+        .trim()
+        .parse()
+        .ok()?;
+    Some(kb * 1024)
+}
+
+impl<L: Language> Context<L> {
+    /// Stops the search once it exceeds `budget`, see [`Context::exceeded_budget`]. The time
+    /// is counted from now.
+    pub fn set_budget(&mut self, budget: Budget) {
+        self.budget = Some(Tracker {
+            budget,
+            start: Instant::now(),
+            checks: AtomicUsize::new(0),
+            exceeded: OnceLock::new(),
+        });
+    }
+
+    /// The budget that the search exceeded, if any. A step of the level saturation checks
+    /// the budget while it runs, and is aborted without effect when it would exceed it,
+    /// so the number of entries never exceeds [`Budget::entries`].
+    pub fn exceeded_budget(&self) -> Option<Exceeded> {
+        self.check_budget(false);
+        self.budget.as_ref()?.exceeded.get().copied()
+    }
+
+    /// Records the first budget that is exceeded. During a step, every distinct formula
+    /// derived so far counts as a new entry, and the memory is only measured every few calls.
+    pub(super) fn check_budget(&self, during_step: bool) {
+        let Some(tracker) = &self.budget else {
+            return;
+        };
+        if tracker.exceeded.get().is_some() {
+            return;
+        }
+        let Budget {
+            time,
+            entries,
+            memory,
+        } = tracker.budget;
+        let derived = if during_step {
+            self.counters.derived.load(Ordering::Relaxed)
+        } else {
+            0
+        };
+        let exceeded = if time.is_some_and(|time| tracker.start.elapsed() > time) {
+            Exceeded::Time
+        } else if entries.is_some_and(|entries| self.entries.len() + derived > entries) {
+            Exceeded::Entries
+        } else if memory.is_some_and(|memory| {
+            (!during_step
+                || tracker.checks.fetch_add(1, Ordering::Relaxed) % MEMORY_CHECK_INTERVAL == 0)
+                && resident_memory().is_some_and(|used| used > memory)
+        }) {
+            Exceeded::Memory
+        } else {
+            return;
+        };
+        let _ = tracker.exceeded.set(exceeded);
+    }
+
+    pub(super) fn is_over_budget(&self) -> bool {
+        self.budget
+            .as_ref()
+            .is_some_and(|tracker| tracker.exceeded.get().is_some())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use crate::{
+        context::Context,
+        formula::{langs::ImpNeg, language::Normal},
+    };
+
+    use super::{Budget, Exceeded};
+
+    #[test]
+    fn stops_at_budget() {
+        let axioms = ImpNeg::lukasiewicz3();
+        let mut context = Context::new(&axioms);
+        context.step(&(|_, _, _| ()));
+        context.step(&(|_, _, _| ()));
+        let entries = context.entries.len();
+        context.set_budget(Budget {
+            entries: Some(entries + 10),
+            ..Budget::default()
+        });
+        assert_eq!(context.exceeded_budget(), None);
+
+        let before: Vec<Normal<ImpNeg>> = context.entries.keys().cloned().collect();
+        let stats = context.step(&(|_, _, _| ()));
+        assert!(stats.interrupted);
+        assert_eq!(stats.exceeded, Some(Exceeded::Entries));
+        assert_eq!(context.generation(), 2);
+        assert_eq!(context.entries.len(), before.len());
+        assert!(before.iter().all(|f| context.entries.contains_key(f)));
+        assert_eq!(context.exceeded_budget(), Some(Exceeded::Entries));
+
+        let mut context = Context::new(&axioms);
+        context.set_budget(Budget {
+            time: Some(Duration::ZERO),
+            ..Budget::default()
+        });
+        std::thread::sleep(Duration::from_millis(1));
+        assert_eq!(context.exceeded_budget(), Some(Exceeded::Time));
+        assert!(context.step(&(|_, _, _| ())).interrupted);
+        assert_eq!(context.entries.len(), axioms.len());
+    }
+}
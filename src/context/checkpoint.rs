@@ -0,0 +1,221 @@
+//! Saving a level saturation to a file, to continue it later.
+
+use std::{
+    fmt::Display,
+    io::{self, Read, Write},
+};
+
+use ahash::HashMap;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    formula::language::{Language, Normal, ParseError},
+    input::Named,
+};
+
+use super::{index_majors, Context, Meta};
+
+/// Increased whenever the format changes
+const VERSION: u32 = 1;
+
+/// Everything about a [`Context`] that the settings do not determine. The term indices
+/// are rebuilt from the entries, and formulas are stored in Polish notation.
+#[derive(Serialize, Deserialize)]
+struct Checkpoint {
+    version: u32,
+    language: String,
+    axioms: Vec<(Option<String>, String)>,
+    generation: usize,
+    next_idx: usize,
+    entries: Vec<(String, Meta)>,
+    retired: Vec<(String, Meta)>,
+    used: Vec<usize>,
+}
+
+#[derive(Debug)]
+pub enum CheckpointError {
+    Io(io::Error),
+    Format(bincode::Error),
+    Version(u32),
+    /// The checkpoint is for the given language
+    Language(String),
+    Axioms,
+    Formula(ParseError),
+}
+
+impl Display for CheckpointError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "{e}"),
+            Self::Format(e) => write!(f, "not a valid checkpoint, {e}"),
+            Self::Version(v) => write!(f, "the checkpoint has version {v} instead of {VERSION}"),
+            Self::Language(l) => write!(f, "the checkpoint is for the language {l}"),
+            Self::Axioms => write!(f, "the checkpoint was made with different axioms"),
+            Self::Formula(e) => write!(f, "the checkpoint contains an invalid formula, {e}"),
+        }
+    }
+}
+
+impl From<bincode::Error> for CheckpointError {
+    fn from(e: bincode::Error) -> Self {
+        match *e {
+            bincode::ErrorKind::Io(e) => Self::Io(e),
+            _ => Self::Format(e),
+        }
+    }
+}
+
+fn axiom_strings<L: Language>(axioms: &[Named<L>]) -> Vec<(Option<String>, String)>
+where
+    L::Variant<()>: Display,
+{
+    axioms
+        .iter()
+        .map(|(name, f)| (name.clone(), f.to_string()))
+        .collect()
+}
+
+impl<L: Language> Context<L>
+where
+    L::Variant<()>: Display + TryFrom<char>,
+{
+    /// Writes the entries and the progress of the level saturation, which must have
+    /// started from `axioms` in `language`.
+    pub fn save(
+        &self,
+        language: &str,
+        axioms: &[Named<L>],
+        writer: impl Write,
+    ) -> Result<(), CheckpointError> {
+        let formulas = |map: &HashMap<Normal<L>, Meta>| {
+            map.iter()
+                .map(|(f, meta)| (f.to_string(), meta.clone()))
+                .collect()
+        };
+        let checkpoint = Checkpoint {
+            version: VERSION,
+            language: language.to_owned(),
+            axioms: axiom_strings(axioms),
+            generation: self.generation,
+            next_idx: self.next_idx,
+            entries: formulas(&self.entries),
+            retired: formulas(&self.retired),
+            used: self.used.iter().copied().collect(),
+        };
+        bincode::serialize_into(writer, &checkpoint)?;
+        Ok(())
+    }
+
+    /// Continues a level saturation saved with [`Context::save`]. Settings such as
+    /// limits and subsumption are not saved and have to be applied again.
+    pub fn load(
+        language: &str,
+        axioms: &[Named<L>],
+        reader: impl Read,
+    ) -> Result<Self, CheckpointError> {
+        let checkpoint: Checkpoint = bincode::deserialize_from(reader)?;
+        if checkpoint.version != VERSION {
+            return Err(CheckpointError::Version(checkpoint.version));
+        }
+        if checkpoint.language != language {
+            return Err(CheckpointError::Language(checkpoint.language));
+        }
+        if checkpoint.axioms != axiom_strings(axioms) {
+            return Err(CheckpointError::Axioms);
+        }
+        let formulas = |list: Vec<(String, Meta)>| {
+            list.into_iter()
+                .map(|(f, meta)| Ok((f.parse().map_err(CheckpointError::Formula)?, meta)))
+                .collect::<Result<HashMap<Normal<L>, Meta>, CheckpointError>>()
+        };
+
+        let mut context = Self::with_names(axioms);
+        context.entries = formulas(checkpoint.entries)?;
+        context.retired = formulas(checkpoint.retired)?;
+        context.by_index = context
+            .entries
+            .iter()
+            .chain(&context.retired)
+            .map(|(f, meta)| (meta.index, (f.clone(), meta.sources[meta.best])))
+            .collect();
+        context.next_idx = checkpoint.next_idx;
+        context.generation = checkpoint.generation;
+        context.used = checkpoint.used.into_iter().collect();
+
+        let mut generations = vec![Vec::new(); context.generation + 1];
+        for (f, meta) in &context.entries {
+            generations[meta.generation].push((f.clone(), meta.index));
+        }
+        context.majors = generations.iter().map(|g| index_majors(g)).collect();
+        context.frontier = generations.pop().unwrap();
+        Ok(context)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use ahash::HashMap;
+
+    use crate::{
+        context::{Context, SourceChoice},
+        formula::{langs::ImpNeg, language::Normal},
+    };
+
+    use super::CheckpointError;
+
+    #[test]
+    fn resumes() {
+        let axioms: Vec<_> = ImpNeg::lukasiewicz3()
+            .into_iter()
+            .map(|f| (None, f))
+            .collect();
+        let generations = |context: &Context<ImpNeg>| -> HashMap<Normal<ImpNeg>, usize> {
+            context
+                .entries
+                .iter()
+                .map(|(f, meta)| (f.clone(), meta.generation))
+                .collect()
+        };
+        for subsumption in [false, true] {
+            let mut context = Context::with_names(&axioms);
+            if subsumption {
+                context.enable_subsumption();
+            }
+            for _ in 0..2 {
+                context.step(&(|_, _, _| ()));
+            }
+            let mut saved = Vec::new();
+            context.save("imp-neg", &axioms, &mut saved).unwrap();
+
+            let mut resumed = Context::load("imp-neg", &axioms, &saved[..]).unwrap();
+            if subsumption {
+                resumed.enable_subsumption();
+            }
+            assert_eq!(resumed.generation(), 2);
+            assert_eq!(generations(&resumed), generations(&context));
+            context.step(&(|_, _, _| ()));
+            resumed.step(&(|_, _, _| ()));
+            assert_eq!(generations(&resumed), generations(&context));
+            let identity = "Cpp".parse().unwrap();
+            assert!(resumed
+                .proof_of(&identity, SourceChoice::Smallest)
+                .is_some());
+        }
+
+        let context = Context::with_names(&axioms);
+        let mut saved = Vec::new();
+        context.save("imp-neg", &axioms, &mut saved).unwrap();
+        assert!(matches!(
+            Context::load("imp-false", &axioms, &saved[..]),
+            Err(CheckpointError::Language(_))
+        ));
+        assert!(matches!(
+            Context::load("imp-neg", &axioms[1..], &saved[..]),
+            Err(CheckpointError::Axioms)
+        ));
+        assert!(matches!(
+            Context::<ImpNeg>::load("imp-neg", &axioms, &saved[..10]),
+            Err(CheckpointError::Io(_))
+        ));
+    }
+}
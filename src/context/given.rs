@@ -0,0 +1,261 @@
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, VecDeque},
+    fmt::Debug,
+    sync::atomic::Ordering,
+};
+
+use ahash::HashMap;
+use rayon::prelude::*;
+
+use crate::formula::{
+    index::TermIndex,
+    language::{Language, Normal},
+};
+
+use super::{Context, StepStats};
+
+/// The state of an Otter-style given-clause search: formulas are derived but not
+/// used as premises until they are picked as the given formula, lightest first.
+pub(super) struct Given<L: Language> {
+    /// Derived formulas that have not been picked yet, by index
+    pending: HashMap<usize, Normal<L>>,
+    by_weight: BinaryHeap<Reverse<(usize, usize)>>,
+    by_age: VecDeque<usize>,
+    /// Picked formulas, indexed by the whole formula
+    minors: TermIndex<L, (Normal<L>, usize)>,
+    /// Picked implications, indexed by their antecedent
+    majors: TermIndex<L, (Normal<L>, usize)>,
+    /// Number of picks by weight for every pick by age
+    ratio: usize,
+    picks: usize,
+}
+
+impl<L: Language> Debug for Given<L> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Given")
+            .field("pending", &self.pending.len())
+            .field("picks", &self.picks)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<L: Language> Given<L> {
+    fn push(&mut self, f: Normal<L>, weight: usize, index: usize) {
+        self.by_weight.push(Reverse((weight, index)));
+        self.by_age.push_back(index);
+        self.pending.insert(index, f);
+    }
+
+    /// Removes the next formula to process from the queues.
+    fn pick(&mut self) -> Option<(Normal<L>, usize)> {
+        let by_age = self.ratio == 0 || self.picks % (self.ratio + 1) == self.ratio;
+        let mut next = || {
+            if by_age {
+                self.by_age.pop_front()
+            } else {
+                self.by_weight.pop().map(|Reverse((_, index))| index)
+            }
+        };
+        // the other queue still contains formulas that were already picked
+        while let Some(index) = next() {
+            if let Some(f) = self.pending.remove(&index) {
+                self.picks += 1;
+                return Some((f, index));
+            }
+        }
+        None
+    }
+
+    /// Undoes the last [`Given::pick`], so that `f` is picked next again.
+    fn unpick(&mut self, f: Normal<L>, weight: usize, index: usize) {
+        self.picks -= 1;
+        self.by_weight.push(Reverse((weight, index)));
+        self.by_age.push_front(index);
+        self.pending.insert(index, f);
+    }
+
+    pub(super) fn pending(&self) -> usize {
+        self.pending.len()
+    }
+}
+
+/// The result of one iteration of the given-clause loop.
+#[derive(Debug)]
+pub struct GivenStep<L: Language> {
+    pub given: Normal<L>,
+    pub weight: usize,
+    pub stats: StepStats,
+}
+
+impl<L: Language> Context<L> {
+    /// Switches from level saturation to a given-clause search, where [`Context::given_step`]
+    /// is used instead of [`Context::step`]. The formula with the lowest weight is picked
+    /// next, except that every `ratio + 1`-th pick is the oldest formula.
+    ///
+    /// Subsumption only drops new formulas in this mode, entries are never retired.
+    pub fn enable_given_clause(&mut self, ratio: usize) {
+        let mut given = Given {
+            pending: HashMap::default(),
+            by_weight: BinaryHeap::new(),
+            by_age: VecDeque::new(),
+            minors: TermIndex::default(),
+            majors: TermIndex::default(),
+            ratio,
+            picks: 0,
+        };
+        let mut entries: Vec<_> = self.entries.iter().map(|(f, m)| (f, m.index)).collect();
+        entries.sort_by_key(|(_, index)| *index);
+        for (f, index) in entries {
+            given.push(f.clone(), self.weight(f), index);
+        }
+        self.given = Some(given);
+    }
+
+    /// Picks the next given formula and applies modus ponens to it and every formula picked
+    /// before. Returns `None` if no formulas are left.
+    ///
+    /// Like [`Context::step`], the step is aborted without effect when it is interrupted or
+    /// exceeds the budget, and the same formula is picked by the next step.
+    ///
+    /// # Panics
+    /// If the given-clause search was not enabled.
+    pub fn given_step<F: Fn(&Normal<L>, &Normal<L>, &Normal<L>) + Send + Sync>(
+        &mut self,
+        for_each_new: &F,
+    ) -> Option<GivenStep<L>> {
+        let mut given = self
+            .given
+            .take()
+            .expect("given-clause search is not enabled");
+        let Some((f, index)) = given.pick() else {
+            self.given = Some(given);
+            return None;
+        };
+        let weight = self.weight(&f);
+
+        given.minors.insert(f.terms(), (f.clone(), index));
+        if let Some(antecedent) = f.antecedent() {
+            given.majors.insert(antecedent, (f.clone(), index));
+        }
+
+        let as_minor = self.partners(f.terms(), std::slice::from_ref(&given.majors));
+        let as_major = f.antecedent().map_or_else(Vec::new, |antecedent| {
+            self.partners(antecedent, std::slice::from_ref(&given.minors))
+        });
+        let new_entries = as_minor
+            .into_par_iter()
+            .filter(|_| !self.should_stop())
+            .filter_map(|(major, i)| self.try_modus_ponens((&f, index), (major, *i), for_each_new))
+            .chain(
+                as_major
+                    .into_par_iter()
+                    // the given formula with itself is already covered above
+                    .filter(|(_, i)| *i != index && !self.should_stop())
+                    .filter_map(|(minor, i)| {
+                        self.try_modus_ponens((minor, *i), (&f, index), for_each_new)
+                    }),
+            )
+            .inspect(|_| {
+                self.counters.derived.fetch_add(1, Ordering::Relaxed);
+            })
+            .collect_vec_list();
+        if self.is_interrupted() {
+            given.minors.remove(f.terms(), |(_, i)| *i == index);
+            if let Some(antecedent) = f.antecedent() {
+                given.majors.remove(antecedent, |(_, i)| *i == index);
+            }
+            given.unpick(f.clone(), weight, index);
+            self.given = Some(given);
+            let mut stats = self.take_stats(0);
+            stats.interrupted = true;
+            stats.exceeded = self.exceeded_budget();
+            return Some(GivenStep {
+                given: f,
+                weight,
+                stats,
+            });
+        }
+
+        self.generation = given.picks;
+        for (f, source) in new_entries.into_iter().flatten() {
+            if let Some((f, index)) = self.insert(f, source) {
+                let weight = self.weight(&f);
+                given.push(f, weight, index);
+            }
+        }
+        self.given = Some(given);
+
+        Some(GivenStep {
+            given: f,
+            weight,
+            stats: self.take_stats(0),
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    };
+
+    use crate::{
+        context::Context,
+        formula::{langs::ImpNeg, language::Normal},
+    };
+
+    #[test]
+    fn finds_identity() {
+        let axioms = ImpNeg::lukasiewicz3();
+        let mut context = Context::new(&axioms);
+        context.enable_given_clause(4);
+        let identity: Normal<ImpNeg> = "Cpp".parse().unwrap();
+        let mut weights = Vec::new();
+        while !context.entries.contains_key(&identity) {
+            let step = context.given_step(&(|_, _, _| ())).unwrap();
+            weights.push(step.weight);
+            assert!(weights.len() < 100);
+        }
+        // the first four picks are by weight
+        assert!(weights[..4].windows(2).all(|w| w[0] <= w[1]));
+    }
+
+    #[test]
+    fn interrupted_steps_have_no_effect() {
+        let mut context = Context::new(&ImpNeg::lukasiewicz3());
+        context.enable_given_clause(4);
+        let flag = Arc::new(AtomicBool::new(false));
+        context.set_interrupt(flag.clone());
+        for _ in 0..3 {
+            context.given_step(&(|_, _, _| ())).unwrap();
+        }
+        let entries = context.entries.clone();
+        let pending = context.pending();
+
+        flag.store(true, Ordering::Relaxed);
+        let step = context.given_step(&(|_, _, _| ())).unwrap();
+        assert!(step.stats.interrupted);
+        assert_eq!(context.generation(), 3);
+        assert_eq!(context.entries, entries);
+        assert_eq!(context.pending(), pending);
+
+        flag.store(false, Ordering::Relaxed);
+        let next = context.given_step(&(|_, _, _| ())).unwrap();
+        assert!(!next.stats.interrupted);
+        assert_eq!(next.given, step.given);
+        assert_eq!(context.generation(), 4);
+    }
+
+    #[test]
+    #[should_panic(expected = "no level saturation steps")]
+    fn has_no_saturation_steps() {
+        let mut context = Context::new(&ImpNeg::lukasiewicz3());
+        context.enable_given_clause(4);
+        for _ in 0..3 {
+            context.given_step(&(|_, _, _| ())).unwrap();
+        }
+        let _ = context.new_entries_iter(&(|_, _, _| ()));
+    }
+}
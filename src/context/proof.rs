@@ -0,0 +1,150 @@
+use ahash::HashMap;
+
+use crate::{
+    dproof::DProof,
+    formula::language::{Language, Normal},
+};
+
+use super::{Context, Source};
+
+/// Which of the sources of an entry a proof uses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SourceChoice {
+    /// The source the entry was added with. In level saturation, this is the smallest
+    /// [`Source`] of the step that derived it, the one with the lowest minor premise index
+    /// and then the lowest major premise index.
+    First,
+    /// The source of the smallest known proof, see [`Meta::size`]
+    ///
+    /// [`Meta::size`]: super::Meta::size
+    Smallest,
+}
+
+/// A proof of a single formula, where every step only depends on earlier steps.
+#[derive(Debug, Clone)]
+pub struct Proof<L: Language> {
+    pub steps: Vec<ProofStep<L>>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ProofStep<L: Language> {
+    pub formula: Normal<L>,
+    /// The index of the entry in the [`Context`]
+    pub index: usize,
+    /// How the step follows from earlier steps, which are numbered from 1
+    pub source: Source,
+}
+
+impl<L: Language> Proof<L> {
+    /// The proof in D-notation, where the axioms are numbered by their index in the
+    /// [`Context`]. Steps that are used more than once are repeated.
+    pub fn to_dproof(&self) -> DProof {
+        let mut dproofs: Vec<DProof> = Vec::with_capacity(self.steps.len());
+        for step in &self.steps {
+            let dproof = match step.source {
+                Source::Axiom => DProof::Axiom(step.index),
+                Source::MP(minor, major) => DProof::D(
+                    Box::new(dproofs[major - 1].clone()),
+                    Box::new(dproofs[minor - 1].clone()),
+                ),
+            };
+            dproofs.push(dproof);
+        }
+        dproofs.pop().unwrap()
+    }
+}
+
+impl<L: Language> Context<L> {
+    /// A proof of the entry `f` with a single source per step, or `None` if `f` is not an entry.
+    pub fn proof_of(&self, f: &Normal<L>, choice: SourceChoice) -> Option<Proof<L>> {
+        let root = self.entries.get(f).or_else(|| self.retired.get(f))?.index;
+        // the first source of an entry only depends on earlier entries, and the best source
+        // only on entries with smaller proofs, so following either never leads to a cycle
+        let source = |index| {
+            let (_, meta) = self.entry(index).unwrap();
+            match choice {
+                SourceChoice::First => meta.sources[0],
+                SourceChoice::Smallest => meta.sources[meta.best],
+            }
+        };
+
+        // order the steps depth-first, so that premises come before their conclusion
+        let mut numbers = HashMap::default();
+        let mut steps = Vec::new();
+        let mut stack = vec![(root, false)];
+        while let Some((index, expanded)) = stack.pop() {
+            if numbers.contains_key(&index) {
+                continue;
+            }
+            if expanded {
+                let source = match source(index) {
+                    Source::Axiom => Source::Axiom,
+                    Source::MP(a, b) => Source::MP(numbers[&a], numbers[&b]),
+                };
+                steps.push(ProofStep {
+                    formula: self.entry(index).unwrap().0.clone(),
+                    index,
+                    source,
+                });
+                numbers.insert(index, steps.len());
+            } else {
+                stack.push((index, true));
+                stack.extend(source(index).premises().map(|i| (i, false)));
+            }
+        }
+        Some(Proof { steps })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        context::{Context, Source},
+        formula::{
+            langs::ImpNeg,
+            language::{modus_ponens, Normal},
+        },
+    };
+
+    use super::SourceChoice;
+
+    #[test]
+    fn proves_identity() {
+        let identity: Normal<ImpNeg> = "Cpp".parse().unwrap();
+        for subsumption in [false, true] {
+            let axioms = ImpNeg::lukasiewicz3();
+            let mut context = Context::new(&axioms);
+            if subsumption {
+                context.enable_subsumption();
+            }
+            for _ in 0..3 {
+                context.step(&(|_, _, _| ()));
+            }
+            let mut sizes = Vec::new();
+            for choice in [SourceChoice::First, SourceChoice::Smallest] {
+                let proof = context.proof_of(&identity, choice).unwrap();
+                assert_eq!(proof.steps.last().unwrap().formula, identity);
+                for (n, step) in proof.steps.iter().enumerate() {
+                    match step.source {
+                        Source::Axiom => assert!(axioms.contains(&step.formula)),
+                        Source::MP(a, b) => {
+                            assert!(a <= n && b <= n);
+                            let (minor, major) = (&proof.steps[a - 1], &proof.steps[b - 1]);
+                            assert_eq!(
+                                modus_ponens(&minor.formula, &major.formula).as_ref(),
+                                Some(&step.formula)
+                            );
+                        }
+                    }
+                }
+                assert_eq!(proof.to_dproof().evaluate(&axioms).unwrap(), identity);
+                let mp_steps = proof.steps.iter().filter(|s| s.source != Source::Axiom);
+                sizes.push(mp_steps.count());
+            }
+            let meta = &context.entries[&identity];
+            assert_eq!(sizes[1], meta.size);
+            assert!(sizes[1] <= sizes[0]);
+            assert!(meta.depth > 0 && meta.depth <= meta.size);
+        }
+    }
+}
@@ -0,0 +1,317 @@
+use std::{
+    fmt::{Debug, Display},
+    str::FromStr,
+};
+
+use ahash::{HashMap, HashSet};
+
+use crate::formula::language::{modus_ponens, Language, Normal};
+
+/// A condensed detachment proof in D-notation, as used by Meredith and Prior.
+///
+/// `Dxy` detaches the minor premise `y` from the major premise `x`, and the digits
+/// `1` to `9` refer to the axioms in the order they are given, so later axioms can not
+/// be used. For example, `DD211` proves `Cpp` from Łukasiewicz's third system.
+/// Earlier theorems can be referred to by name in brackets, as in `D[syl]1`, and axioms
+/// after the ninth by their number in brackets, as in `D[10]1`, so names that are
+/// positive numbers refer to axioms rather than theorems.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DProof {
+    /// The axiom with the given index, counting from 0
+    Axiom(usize),
+    Lemma(String),
+    D(Box<DProof>, Box<DProof>),
+}
+
+/// Theorems that proofs can refer to by name, together with the conclusions of the
+/// detachments in their proofs.
+pub type Lemmas<L> = HashMap<String, (Normal<L>, HashSet<Normal<L>>)>;
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct DParseError {
+    /// The 0-based position of the unexpected character
+    pub position: usize,
+    /// The unexpected character, `None` if the proof ended early
+    pub found: Option<char>,
+}
+
+impl Display for DParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.found {
+            Some(c) => write!(f, "unexpected `{c}` at position {}", self.position),
+            None => write!(f, "proof ends early at position {}", self.position),
+        }
+    }
+}
+
+pub enum DEvalError<L: Language> {
+    UnknownAxiom {
+        position: usize,
+        axiom: usize,
+    },
+    UnknownLemma {
+        position: usize,
+        name: String,
+    },
+    NotDetachable {
+        position: usize,
+        major: Normal<L>,
+        minor: Normal<L>,
+    },
+}
+
+impl<L: Language> Debug for DEvalError<L> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnknownAxiom { position, axiom } => f
+                .debug_struct("UnknownAxiom")
+                .field("position", position)
+                .field("axiom", axiom)
+                .finish(),
+            Self::UnknownLemma { position, name } => f
+                .debug_struct("UnknownLemma")
+                .field("position", position)
+                .field("name", name)
+                .finish(),
+            Self::NotDetachable {
+                position,
+                major,
+                minor,
+            } => f
+                .debug_struct("NotDetachable")
+                .field("position", position)
+                .field("major", major)
+                .field("minor", minor)
+                .finish(),
+        }
+    }
+}
+
+impl<L: Language> Display for DEvalError<L>
+where
+    L::Variant<()>: Display,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnknownAxiom { position, axiom } => {
+                write!(f, "position {position}: there is no axiom {}", axiom + 1)
+            }
+            Self::UnknownLemma { position, name } => {
+                write!(f, "position {position}: there is no theorem `{name}`")
+            }
+            Self::NotDetachable {
+                position,
+                major,
+                minor,
+            } => write!(
+                f,
+                "position {position}: {minor} can not be detached from {major}"
+            ),
+        }
+    }
+}
+
+impl Display for DProof {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Axiom(i) if *i < 9 => write!(f, "{}", i + 1),
+            Self::Axiom(i) => write!(f, "[{}]", i + 1),
+            Self::Lemma(name) => write!(f, "[{name}]"),
+            Self::D(major, minor) => write!(f, "D{major}{minor}"),
+        }
+    }
+}
+
+impl FromStr for DProof {
+    type Err = DParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut chars = s.chars().enumerate();
+        // the subproofs that are still missing a minor premise, and the major premise if it is known
+        let mut open: Vec<Option<DProof>> = Vec::new();
+        loop {
+            let Some((position, c)) = chars.next() else {
+                return Err(DParseError {
+                    position: s.chars().count(),
+                    found: None,
+                });
+            };
+            let mut proof = match c {
+                'D' => {
+                    open.push(None);
+                    continue;
+                }
+                '1'..='9' => Self::Axiom(c as usize - '1' as usize),
+                '[' => {
+                    let name: String = chars
+                        .by_ref()
+                        .map(|(_, c)| c)
+                        .take_while(|&c| c != ']')
+                        .collect();
+                    if s.chars().nth(position + name.chars().count() + 1) != Some(']') {
+                        return Err(DParseError {
+                            position: s.chars().count(),
+                            found: None,
+                        });
+                    }
+                    match name.parse::<usize>() {
+                        Ok(axiom) if axiom > 0 => Self::Axiom(axiom - 1),
+                        _ => Self::Lemma(name),
+                    }
+                }
+                _ => {
+                    return Err(DParseError {
+                        position,
+                        found: Some(c),
+                    })
+                }
+            };
+            // combine the finished proof with the open ones
+            loop {
+                match open.pop() {
+                    None => {
+                        return match chars.next() {
+                            None => Ok(proof),
+                            Some((position, c)) => Err(DParseError {
+                                position,
+                                found: Some(c),
+                            }),
+                        };
+                    }
+                    Some(None) => {
+                        open.push(Some(proof));
+                        break;
+                    }
+                    Some(Some(major)) => proof = Self::D(Box::new(major), Box::new(proof)),
+                }
+            }
+        }
+    }
+}
+
+impl DProof {
+    /// The theorem that the proof derives from `axioms`, or the first step that fails.
+    pub fn evaluate<L: Language>(&self, axioms: &[Normal<L>]) -> Result<Normal<L>, DEvalError<L>> {
+        self.evaluate_with(axioms, &HashMap::default(), &mut HashSet::default())
+    }
+
+    /// Like [`DProof::evaluate`], but the proof may refer to `lemmas`. The conclusion of
+    /// every detachment, including those in the proofs of lemmas, is added to `steps`.
+    pub fn evaluate_with<L: Language>(
+        &self,
+        axioms: &[Normal<L>],
+        lemmas: &Lemmas<L>,
+        steps: &mut HashSet<Normal<L>>,
+    ) -> Result<Normal<L>, DEvalError<L>> {
+        self.evaluate_at(axioms, lemmas, steps, &mut 0)
+    }
+
+    /// Evaluates the proof that starts at `position` in D-notation, and moves `position`
+    /// past it.
+    fn evaluate_at<L: Language>(
+        &self,
+        axioms: &[Normal<L>],
+        lemmas: &Lemmas<L>,
+        steps: &mut HashSet<Normal<L>>,
+        position: &mut usize,
+    ) -> Result<Normal<L>, DEvalError<L>> {
+        let start = *position;
+        *position += 1;
+        match self {
+            Self::Axiom(i) => {
+                if *i >= 9 {
+                    *position += (i + 1).to_string().len() + 1;
+                }
+                axioms.get(*i).cloned().ok_or(DEvalError::UnknownAxiom {
+                    position: start,
+                    axiom: *i,
+                })
+            }
+            Self::Lemma(name) => {
+                *position += name.chars().count() + 1;
+                let (theorem, lemma_steps) =
+                    lemmas.get(name).ok_or_else(|| DEvalError::UnknownLemma {
+                        position: start,
+                        name: name.clone(),
+                    })?;
+                steps.extend(lemma_steps.iter().cloned());
+                Ok(theorem.clone())
+            }
+            Self::D(major, minor) => {
+                let major = major.evaluate_at(axioms, lemmas, steps, position)?;
+                let minor = minor.evaluate_at(axioms, lemmas, steps, position)?;
+                let theorem = modus_ponens(&minor, &major).ok_or(DEvalError::NotDetachable {
+                    position: start,
+                    major,
+                    minor,
+                })?;
+                steps.insert(theorem.clone());
+                Ok(theorem)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::formula::{langs::ImpNeg, language::Normal};
+
+    use super::{DEvalError, DParseError, DProof};
+
+    #[test]
+    fn parse_and_evaluate() {
+        let axioms = ImpNeg::lukasiewicz3();
+        let proof: DProof = "DD211".parse().unwrap();
+        assert_eq!(proof.to_string(), "DD211");
+        let identity: Normal<ImpNeg> = "Cpp".parse().unwrap();
+        assert_eq!(proof.evaluate(&axioms).unwrap(), identity);
+
+        let error = |position, found| Err(DParseError { position, found });
+        assert_eq!("D1".parse::<DProof>(), error(2, None));
+        assert_eq!("D111".parse::<DProof>(), error(3, Some('1')));
+        assert_eq!("D1x".parse::<DProof>(), error(2, Some('x')));
+        assert_eq!("0".parse::<DProof>(), error(0, Some('0')));
+
+        let proof: DProof = "D1D31".parse().unwrap();
+        assert!(matches!(
+            proof.evaluate(&axioms),
+            Err(DEvalError::NotDetachable { position: 2, .. })
+        ));
+        assert_eq!("D1[ab".parse::<DProof>(), error(5, None));
+        assert_eq!(
+            "D[syl]1".parse::<DProof>(),
+            Ok(DProof::D(
+                Box::new(DProof::Lemma("syl".to_owned())),
+                Box::new(DProof::Axiom(0))
+            ))
+        );
+        let proof = DProof::D(Box::new(DProof::Axiom(9)), Box::new(DProof::Axiom(0)));
+        assert_eq!(proof.to_string(), "D[10]1");
+        assert_eq!("D[10]1".parse::<DProof>(), Ok(proof));
+        assert!(matches!(
+            "DD[10]11".parse::<DProof>().unwrap().evaluate(&axioms),
+            Err(DEvalError::UnknownAxiom {
+                position: 2,
+                axiom: 9
+            })
+        ));
+        let ten: Vec<_> = axioms.iter().cycle().take(10).cloned().collect();
+        assert!(matches!(
+            "DD[10]1[11]".parse::<DProof>().unwrap().evaluate(&ten),
+            Err(DEvalError::UnknownAxiom {
+                position: 7,
+                axiom: 10
+            })
+        ));
+        assert_eq!("[0]".parse::<DProof>(), Ok(DProof::Lemma("0".to_owned())));
+
+        let proof: DProof = "DD214".parse().unwrap();
+        assert!(matches!(
+            proof.evaluate(&axioms),
+            Err(DEvalError::UnknownAxiom {
+                position: 4,
+                axiom: 3
+            })
+        ));
+    }
+}
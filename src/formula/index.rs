@@ -0,0 +1,250 @@
+use std::collections::HashMap;
+
+use super::language::{Language, Term};
+
+/// Number of symbols of a term that are used as its key. Terms that agree on
+/// their first `MAX_KEY_LEN` symbols end up in the same leaf.
+const MAX_KEY_LEN: usize = 16;
+
+#[derive(Debug)]
+enum Key<L: Language> {
+    Var,
+    Term(L::Variant<()>),
+}
+
+impl<L: Language> Clone for Key<L> {
+    fn clone(&self) -> Self {
+        match self {
+            Self::Var => Self::Var,
+            Self::Term(t) => Self::Term(t.clone()),
+        }
+    }
+}
+
+impl<L: Language> PartialEq for Key<L> {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Var, Self::Var) => true,
+            (Self::Term(a), Self::Term(b)) => a == b,
+            _ => false,
+        }
+    }
+}
+
+impl<L: Language> Eq for Key<L> {}
+
+impl<L: Language> std::hash::Hash for Key<L> {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        core::mem::discriminant(self).hash(state);
+        if let Self::Term(t) = self {
+            t.hash(state);
+        }
+    }
+}
+
+impl<L: Language> Key<L> {
+    fn new(term: &Term<L, ()>) -> Self {
+        match term {
+            Term::Var(_) => Self::Var,
+            Term::Term(t) => Self::Term(t.clone()),
+        }
+    }
+
+    fn arity(&self) -> usize {
+        match self {
+            Self::Var => 0,
+            Self::Term(t) => L::children(t).len(),
+        }
+    }
+}
+
+/// The number of `Term`s that make up the subterm starting at `terms[0]`.
+pub fn subterm_len<L: Language>(terms: &[Term<L, ()>]) -> usize {
+    let mut missing = 1;
+    let mut len = 0;
+    while missing > 0 {
+        missing -= 1;
+        if let Term::Term(t) = &terms[len] {
+            missing += L::children(t).len();
+        }
+        len += 1;
+    }
+    len
+}
+
+/// A discrimination tree over formulas in Polish notation, where every variable is
+/// replaced by the same wildcard. It is used to retrieve the stored values whose key
+/// could possibly unify with a query, without running the unification.
+#[derive(Debug)]
+pub struct TermIndex<L: Language, V> {
+    root: Node<L, V>,
+    len: usize,
+}
+
+#[derive(Debug)]
+struct Node<L: Language, V> {
+    children: HashMap<Key<L>, Node<L, V>>,
+    values: Vec<V>,
+}
+
+impl<L: Language, V> Default for Node<L, V> {
+    fn default() -> Self {
+        Self {
+            children: HashMap::new(),
+            values: Vec::new(),
+        }
+    }
+}
+
+impl<L: Language, V> Default for TermIndex<L, V> {
+    fn default() -> Self {
+        Self {
+            root: Node::default(),
+            len: 0,
+        }
+    }
+}
+
+impl<L: Language, V> TermIndex<L, V> {
+    /// Number of values stored in the index
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn insert(&mut self, key: &[Term<L, ()>], value: V) {
+        let node = key
+            .iter()
+            .take(MAX_KEY_LEN)
+            .fold(&mut self.root, |node, t| {
+                node.children.entry(Key::new(t)).or_default()
+            });
+        node.values.push(value);
+        self.len += 1;
+    }
+
+    /// Removes the values with the given key for which `remove` returns true.
+    pub fn remove(&mut self, key: &[Term<L, ()>], mut remove: impl FnMut(&V) -> bool) {
+        let mut node = &mut self.root;
+        for t in key.iter().take(MAX_KEY_LEN) {
+            let Some(child) = node.children.get_mut(&Key::new(t)) else {
+                return;
+            };
+            node = child;
+        }
+        let before = node.values.len();
+        node.values.retain(|v| !remove(v));
+        self.len -= before - node.values.len();
+    }
+
+    /// Calls `f` with every value whose key may unify with `query`.
+    /// Values whose key can not unify with `query` are mostly, but not always, skipped.
+    pub fn unifiable<'a>(&'a self, query: &[Term<L, ()>], f: &mut impl FnMut(&'a V)) {
+        self.root.retrieve(Retrieve::Unifiable, query, 0, f);
+    }
+
+    /// Calls `f` with every value whose key may be more general than `query`.
+    pub fn generalizations<'a>(&'a self, query: &[Term<L, ()>], f: &mut impl FnMut(&'a V)) {
+        self.root.retrieve(Retrieve::Generalizations, query, 0, f);
+    }
+
+    /// Calls `f` with every value whose key may be an instance of `query`.
+    pub fn instances<'a>(&'a self, query: &[Term<L, ()>], f: &mut impl FnMut(&'a V)) {
+        self.root.retrieve(Retrieve::Instances, query, 0, f);
+    }
+}
+
+/// Which keys to retrieve for a query. Variables in the query may only be
+/// instantiated for `Unifiable` and `Instances`, variables in the keys only for
+/// `Unifiable` and `Generalizations`.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Retrieve {
+    Unifiable,
+    Generalizations,
+    Instances,
+}
+
+impl<L: Language, V> Node<L, V> {
+    fn retrieve<'a>(
+        &'a self,
+        mode: Retrieve,
+        query: &[Term<L, ()>],
+        depth: usize,
+        f: &mut impl FnMut(&'a V),
+    ) {
+        if query.is_empty() || depth == MAX_KEY_LEN {
+            self.values.iter().for_each(f);
+            return;
+        }
+        match &query[0] {
+            Term::Var(_) if mode == Retrieve::Generalizations => {
+                if let Some(child) = self.children.get(&Key::Var) {
+                    child.retrieve(mode, &query[1..], depth + 1, f);
+                }
+            }
+            Term::Var(_) => self.skip(1, depth, &mut |node, depth| {
+                node.retrieve(mode, &query[1..], depth, f);
+            }),
+            Term::Term(t) => {
+                if mode != Retrieve::Instances {
+                    if let Some(child) = self.children.get(&Key::Var) {
+                        child.retrieve(mode, &query[subterm_len(query)..], depth + 1, f);
+                    }
+                }
+                for (key, child) in &self.children {
+                    if let Key::Term(k) = key {
+                        if L::matches(k, t) {
+                            child.retrieve(mode, &query[1..], depth + 1, f);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Calls `f` with every node that is reached by skipping `missing` subterms.
+    /// Nodes where the key was cut off are reached as well.
+    fn skip<'a>(&'a self, missing: usize, depth: usize, f: &mut impl FnMut(&'a Self, usize)) {
+        if missing == 0 || depth == MAX_KEY_LEN {
+            f(self, depth);
+            return;
+        }
+        for (key, child) in &self.children {
+            child.skip(missing - 1 + key.arity(), depth + 1, f);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::formula::{
+        langs::ImpNeg,
+        language::{modus_ponens, Normal},
+    };
+
+    use super::TermIndex;
+
+    #[test]
+    fn finds_all_unifiable() {
+        let formulas: Vec<Normal<ImpNeg>> = ImpNeg::frege()
+            .into_iter()
+            .chain(ImpNeg::russell())
+            .chain(ImpNeg::hilbert())
+            .collect();
+        let mut index = TermIndex::default();
+        for f in &formulas {
+            if let Some(antecedent) = f.antecedent() {
+                index.insert(antecedent, f);
+            }
+        }
+        for p in &formulas {
+            let mut retrieved = Vec::new();
+            index.unifiable(p.terms(), &mut |f| retrieved.push(*f));
+            for f in &formulas {
+                if modus_ponens(p, f).is_some() {
+                    assert!(retrieved.contains(&f), "{f:?} missing for {p:?}");
+                }
+            }
+            assert!(retrieved.len() < index.len());
+        }
+    }
+}
@@ -0,0 +1,247 @@
+//! Infix notation, such as `(p -> q) -> -q -> -p`.
+//!
+//! Binary connectives associate to the right and bind weaker than unary ones, so only
+//! the parentheses that are needed are printed. Variables are letters, optionally
+//! followed by a number.
+
+use std::{
+    collections::HashMap,
+    fmt::{Display, Formatter},
+};
+
+use super::language::{var_name, Language, Normal, Term};
+
+/// A language whose connectives can be written in infix notation.
+pub trait Infix: Language {
+    /// Every connective with its symbols. The first symbol is used for printing, all of
+    /// them are accepted when parsing.
+    const CONNECTIVES: &'static [(Self::Variant<()>, &'static [&'static str])];
+}
+
+/// Displays a formula in infix notation.
+pub struct Infixed<'a, L: Language>(pub &'a [Term<L, ()>]);
+
+fn symbol<L: Infix>(v: &L::Variant<()>) -> &'static str {
+    L::CONNECTIVES
+        .iter()
+        .find(|(c, _)| L::matches(c, v))
+        .map(|(_, symbols)| symbols[0])
+        .expect("every connective has a symbol")
+}
+
+/// Writes the formula at the start of `terms`, and returns its length.
+fn write_infix<L: Infix>(
+    f: &mut Formatter<'_>,
+    terms: &[Term<L, ()>],
+    parenthesize: bool,
+) -> Result<usize, std::fmt::Error> {
+    let t = match &terms[0] {
+        &Term::Var(x) => {
+            write!(f, "{}", var_name(x))?;
+            return Ok(1);
+        }
+        Term::Term(t) => t,
+    };
+    let mut len = 1;
+    match L::children(t).len() {
+        0 => write!(f, "{}", symbol::<L>(t))?,
+        1 => {
+            write!(f, "{}", symbol::<L>(t))?;
+            len += write_infix(f, &terms[1..], true)?;
+        }
+        2 => {
+            if parenthesize {
+                write!(f, "(")?;
+            }
+            len += write_infix(f, &terms[1..], true)?;
+            write!(f, " {} ", symbol::<L>(t))?;
+            // the same connective on the right needs no parentheses
+            let right = match &terms[len] {
+                Term::Term(r) => !L::matches(r, t),
+                Term::Var(_) => false,
+            };
+            len += write_infix(f, &terms[len..], right)?;
+            if parenthesize {
+                write!(f, ")")?;
+            }
+        }
+        _ => unreachable!("infix notation only has unary and binary connectives"),
+    }
+    Ok(len)
+}
+
+impl<L: Infix> Display for Infixed<'_, L> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write_infix(f, self.0, false).map(|_| ())
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct InfixError {
+    /// The 0-based position of the unexpected character
+    pub position: usize,
+    /// The unexpected character, `None` if the formula ended early
+    pub found: Option<char>,
+}
+
+impl Display for InfixError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self.found {
+            Some(c) => write!(f, "unexpected `{c}` at position {}", self.position),
+            None => write!(f, "formula ends early at position {}", self.position),
+        }
+    }
+}
+
+struct Parser<'a, L: Infix> {
+    chars: &'a [char],
+    position: usize,
+    vars: HashMap<String, u16>,
+    language: std::marker::PhantomData<L>,
+}
+
+impl<L: Infix> Parser<'_, L> {
+    fn error(&self) -> InfixError {
+        InfixError {
+            position: self.position,
+            found: self.chars.get(self.position).copied(),
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self
+            .chars
+            .get(self.position)
+            .is_some_and(|c| c.is_whitespace())
+        {
+            self.position += 1;
+        }
+    }
+
+    /// Reads the longest symbol of a connective with `arity` arguments.
+    fn connective(&mut self, arity: usize) -> Option<L::Variant<()>> {
+        let rest = &self.chars[self.position..];
+        let (v, len) = L::CONNECTIVES
+            .iter()
+            .filter(|(v, _)| L::children(v).len() == arity)
+            .flat_map(|(v, symbols)| symbols.iter().map(move |s| (v, s)))
+            .filter(|(_, s)| rest.iter().copied().take(s.chars().count()).eq(s.chars()))
+            .map(|(v, s)| (v, s.chars().count()))
+            .max_by_key(|&(_, len)| len)?;
+        self.position += len;
+        Some(v.clone())
+    }
+
+    fn formula(&mut self) -> Result<Vec<Term<L, ()>>, InfixError> {
+        let left = self.operand()?;
+        self.skip_whitespace();
+        let Some(v) = self.connective(2) else {
+            return Ok(left);
+        };
+        let right = self.formula()?;
+        let mut terms = Vec::with_capacity(1 + left.len() + right.len());
+        terms.push(Term::Term(v));
+        terms.extend(left);
+        terms.extend(right);
+        Ok(terms)
+    }
+
+    fn operand(&mut self) -> Result<Vec<Term<L, ()>>, InfixError> {
+        self.skip_whitespace();
+        if self.chars.get(self.position) == Some(&'(') {
+            self.position += 1;
+            let terms = self.formula()?;
+            self.skip_whitespace();
+            if self.chars.get(self.position) != Some(&')') {
+                return Err(self.error());
+            }
+            self.position += 1;
+            return Ok(terms);
+        }
+        if let Some(v) = self.connective(1) {
+            let mut terms = vec![Term::Term(v)];
+            terms.extend(self.operand()?);
+            return Ok(terms);
+        }
+        if let Some(v) = self.connective(0) {
+            return Ok(vec![Term::Term(v)]);
+        }
+        let start = self.position;
+        if self
+            .chars
+            .get(self.position)
+            .is_some_and(char::is_ascii_lowercase)
+        {
+            self.position += 1;
+            while self
+                .chars
+                .get(self.position)
+                .is_some_and(char::is_ascii_digit)
+            {
+                self.position += 1;
+            }
+            let name: String = self.chars[start..self.position].iter().collect();
+            let next = u16::try_from(self.vars.len()).unwrap();
+            return Ok(vec![Term::Var(*self.vars.entry(name).or_insert(next))]);
+        }
+        Err(self.error())
+    }
+}
+
+/// Parses a formula in infix notation.
+pub fn parse_infix<L: Infix>(s: &str) -> Result<Normal<L>, InfixError> {
+    let chars: Vec<char> = s.chars().collect();
+    let mut parser = Parser {
+        chars: &chars,
+        position: 0,
+        vars: HashMap::new(),
+        language: std::marker::PhantomData,
+    };
+    // the variables are numbered from left to right, which is their order in Polish notation
+    let terms = parser.formula()?;
+    parser.skip_whitespace();
+    if parser.position < chars.len() {
+        return Err(parser.error());
+    }
+    Ok(Normal::from(terms.into_boxed_slice()))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::formula::{
+        langs::{ImpFalse, ImpNeg},
+        language::Normal,
+    };
+
+    use super::{parse_infix, InfixError, Infixed};
+
+    #[test]
+    fn infix() {
+        let cases = [
+            ("p -> q -> p", "CpCqp"),
+            ("(p -> q) -> (q -> r) -> p -> r", "CCpqCCqrCpr"),
+            ("(-p -> p) -> p", "CCNppp"),
+            ("--p -> p", "CNNpp"),
+            ("-(p -> -q) -> p", "CNCpNqp"),
+        ];
+        for (infix, polish) in cases {
+            let f: Normal<ImpNeg> = polish.parse().unwrap();
+            assert_eq!(parse_infix::<ImpNeg>(infix), Ok(f.clone()));
+            assert_eq!(Infixed(f.terms()).to_string(), infix);
+        }
+        assert_eq!(
+            parse_infix::<ImpNeg>("((a→¬b)) → a1"),
+            Ok("CCpNqr".parse().unwrap())
+        );
+
+        let f: Normal<ImpFalse> = "CCCpFFp".parse().unwrap();
+        assert_eq!(Infixed(f.terms()).to_string(), "((p -> F) -> F) -> p");
+        assert_eq!(parse_infix::<ImpFalse>("((p → ⊥) → ⊥) → p"), Ok(f));
+
+        let error = |position, found| Err(InfixError { position, found });
+        assert_eq!(parse_infix::<ImpNeg>("p ->"), error(4, None));
+        assert_eq!(parse_infix::<ImpNeg>("(p -> q"), error(7, None));
+        assert_eq!(parse_infix::<ImpNeg>("p q"), error(2, Some('q')));
+        assert_eq!(parse_infix::<ImpNeg>("p -> F"), error(5, Some('F')));
+    }
+}
@@ -1,6 +1,11 @@
 use std::{fmt::Display, mem};
 
-use crate::formula::language::{Language, Normal, Simple, Term};
+use crate::formula::{
+    infix::Infix,
+    language::{Language, Normal, Simple, Term},
+};
+
+use super::{System, Systems};
 
 pub struct ImpFalse;
 
@@ -11,7 +16,8 @@ pub enum Variants<S> {
 }
 
 impl Language for ImpFalse {
-    type Variant<S> = Variants<S>
+    type Variant<S>
+        = Variants<S>
     where
         S: Simple;
 
@@ -53,6 +59,13 @@ impl Display for Variants<()> {
     }
 }
 
+impl Infix for ImpFalse {
+    const CONNECTIVES: &'static [(Variants<()>, &'static [&'static str])] = &[
+        (Variants::Implication([(), ()]), &["->", "→"]),
+        (Variants::False, &["F", "⊥"]),
+    ];
+}
+
 impl TryFrom<char> for Variants<()> {
     type Error = ();
 
@@ -66,7 +79,7 @@ impl TryFrom<char> for Variants<()> {
 }
 
 impl ImpFalse {
-    pub fn church() -> Box<[Normal<ImpFalse>]> {
+    pub fn church() -> Vec<Normal<ImpFalse>> {
         [
             // a -> (b -> a)
             "CaCba".parse().unwrap(),
@@ -78,7 +91,12 @@ impl ImpFalse {
         .into()
     }
 
-    pub fn meredith1() -> Box<[Normal<ImpFalse>]> {
+    pub fn meredith1() -> Vec<Normal<ImpFalse>> {
         ["CCCCCabCcFdeCCeaCca".parse().unwrap()].into()
     }
 }
+
+impl Systems for ImpFalse {
+    const SYSTEMS: &'static [System<Self>] =
+        &[("church", Self::church), ("meredith1", Self::meredith1)];
+}
@@ -1,7 +1,12 @@
 #![allow(dead_code, clippy::enum_glob_use)]
 use std::{fmt::Display, hash::Hash, mem};
 
-use crate::formula::language::{Language, Normal, Simple, Term};
+use crate::formula::{
+    infix::Infix,
+    language::{Language, Normal, Simple, Term},
+};
+
+use super::{System, Systems};
 
 #[derive(PartialEq, Eq, Clone, Hash, Debug)]
 pub enum Variants<S> {
@@ -55,6 +60,13 @@ impl Display for Variants<()> {
     }
 }
 
+impl Infix for ImpNeg {
+    const CONNECTIVES: &'static [(Variants<()>, &'static [&'static str])] = &[
+        (Variants::Implication([(), ()]), &["->", "→"]),
+        (Variants::Negation([()]), &["-", "¬"]),
+    ];
+}
+
 impl TryFrom<char> for Variants<()> {
     type Error = ();
 
@@ -93,7 +105,7 @@ impl ImpNeg {
             .into()
     }
 
-    pub fn frege() -> Box<[Normal<ImpNeg>]> {
+    pub fn frege() -> Vec<Normal<ImpNeg>> {
         [
             // p -> (q -> p)
             "CpCqp",
@@ -148,3 +160,16 @@ impl ImpNeg {
         ["CCCCCpqCNrNsrtCCtpCsp".parse().unwrap()].into()
     }
 }
+
+impl Systems for ImpNeg {
+    const SYSTEMS: &'static [System<Self>] = &[
+        ("meredith", Self::meredith),
+        ("lukasiewicz1", Self::lukasiewicz1),
+        ("lukasiewicz2", Self::lukasiewicz2),
+        ("lukasiewicz3", Self::lukasiewicz3),
+        ("frege", Self::frege),
+        ("hilbert", Self::hilbert),
+        ("russell", Self::russell),
+        ("lukasiewicz-tarski", Self::lukasiewicz_tarski),
+    ];
+}
@@ -4,3 +4,21 @@ mod imp_false;
 mod imp_neg;
 pub use imp_false::ImpFalse;
 pub use imp_neg::ImpNeg;
+
+use super::language::{Language, Normal};
+
+/// A built-in axiom system: its name and a constructor for its axioms.
+pub type System<L> = (&'static str, fn() -> Vec<Normal<L>>);
+
+/// The named axiom systems that are built in for a language.
+pub trait Systems: Language + Sized {
+    /// The first system is the default one.
+    const SYSTEMS: &'static [System<Self>];
+
+    fn system(name: &str) -> Option<Vec<Normal<Self>>> {
+        Self::SYSTEMS
+            .iter()
+            .find(|(n, _)| *n == name)
+            .map(|(_, axioms)| axioms())
+    }
+}
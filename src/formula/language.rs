@@ -3,8 +3,11 @@ use std::{
     fmt::{Debug, Display},
     hash::Hash,
     str::FromStr,
+    sync::Arc,
 };
 
+use super::index::subterm_len;
+
 pub trait Simple: Clone + Hash + PartialEq + Eq + Send + Sync + Debug {}
 
 impl<T> Simple for T where T: Clone + Hash + PartialEq + Eq + Send + Sync + Debug {}
@@ -26,6 +29,16 @@ pub trait Language: 'static {
     ) -> Self::Variant<T>;
 }
 
+const LETTERS: &[u8; 26] = b"pqrstuvwxyzabcdefghijklmno";
+
+/// The name of the variable `x`: `p` to `z`, then `a` to `o`, then `p1`, `p2` and so on.
+pub fn var_name(x: u16) -> String {
+    match LETTERS.get(x as usize) {
+        Some(&letter) => char::from(letter).to_string(),
+        None => format!("p{}", x as usize - LETTERS.len() + 1),
+    }
+}
+
 pub enum Term<L: Language, S: Simple> {
     Term(L::Variant<S>),
     Var(u16),
@@ -40,6 +53,18 @@ impl<L: Language, S: Simple> Debug for Term<L, S> {
     }
 }
 
+impl<L: Language> Display for Term<L, ()>
+where
+    L::Variant<()>: Display,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Term::Term(v) => write!(f, "{v}"),
+            Term::Var(x) => write!(f, "{}", var_name(*x)),
+        }
+    }
+}
+
 impl<L: Language, S: Simple> Eq for Term<L, S> {}
 
 impl<L: Language, S: Simple> Clone for Term<L, S> {
@@ -71,7 +96,12 @@ impl<L: Language, S: Simple> PartialEq for Term<L, S> {
     }
 }
 
-pub struct Normal<L: Language>(Box<[Term<L, ()>]>);
+/// A formula with normalized variables. The terms are shared between clones, so the
+/// maps and indices of a search can hold the same formula without copying it.
+pub struct Normal<L: Language>(Arc<[Term<L, ()>]>);
+
+/// Maps each variable to the subterm it is replaced with, if any.
+pub type Substitution<'a, L> = Vec<Option<&'a [Term<L, ()>]>>;
 
 impl<L: Language> Debug for Normal<L> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -104,20 +134,77 @@ where
     L::Variant<()>: Display,
 {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        for t in &self.0 {
-            match t {
-                Term::Term(v) => write!(f, "{v}")?,
-                Term::Var(x) => write!(f, "{x}")?,
-            }
-        }
-        Ok(())
+        self.0.iter().try_for_each(|t| write!(f, "{t}"))
     }
 }
 
-#[allow(dead_code)]
-#[derive(Debug)]
+#[derive(Debug, PartialEq, Eq)]
 pub enum ParseError {
-    WrongArguments(isize),
+    Empty,
+    /// The formula ends before the connective at `position` has all its arguments
+    MissingArguments {
+        position: usize,
+        connective: char,
+        arity: usize,
+        found: usize,
+    },
+    /// The formula is already complete before `position`
+    ExtraSymbol {
+        position: usize,
+        found: char,
+    },
+    /// A character that is neither a connective nor one of the allowed variables
+    UnknownSymbol {
+        position: usize,
+        found: char,
+    },
+}
+
+impl ParseError {
+    /// The 0-based position of the character the error is about
+    pub fn position(&self) -> Option<usize> {
+        match self {
+            Self::Empty => None,
+            Self::MissingArguments { position, .. }
+            | Self::ExtraSymbol { position, .. }
+            | Self::UnknownSymbol { position, .. } => Some(*position),
+        }
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Empty => write!(f, "the formula is empty"),
+            Self::MissingArguments {
+                position,
+                connective,
+                arity,
+                found,
+            } => write!(
+                f,
+                "`{connective}` at position {position} expects {arity} argument(s), \
+                 but the formula ends after {found}"
+            ),
+            Self::ExtraSymbol { position, found } => write!(
+                f,
+                "unexpected `{found}` at position {position} after a complete formula"
+            ),
+            Self::UnknownSymbol { position, found } => write!(
+                f,
+                "`{found}` at position {position} is neither a connective nor a variable"
+            ),
+        }
+    }
+}
+
+/// Which characters start a variable in Polish notation.
+#[derive(Debug, Clone, Copy)]
+pub enum Variables<'a> {
+    /// Every character that is not a connective
+    Any,
+    /// Only the given characters, so that typos are reported
+    Only(&'a str),
 }
 
 impl<L: Language> FromStr for Normal<L>
@@ -126,45 +213,92 @@ where
 {
     type Err = ParseError;
 
-    #[allow(clippy::cast_possible_wrap)]
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut num_left: isize = 1;
+        Self::parse_with(s, Variables::Any)
+    }
+}
+
+impl<L: Language> Normal<L>
+where
+    L::Variant<()>: TryFrom<char>,
+{
+    /// Reads a formula in Polish notation. Letters may be followed by a number, as in
+    /// `p12`, which is how [`var_name`] names variables.
+    pub fn parse_with(s: &str, variables: Variables<'_>) -> Result<Self, ParseError> {
         let mut next_var = 0;
         let mut vars_map = HashMap::new();
-        let f: Normal<_> = s
-            .chars()
-            .map(|c| {
-                num_left -= 1;
-                if let Ok(v) = c.try_into() {
-                    num_left += L::children(&v).len() as isize;
-                    Term::Term(v)
-                } else {
-                    let e = vars_map.entry(c);
-                    let n = e.or_insert_with(|| {
-                        let n = next_var;
-                        next_var += 1;
-                        n
-                    });
-                    Term::Var(*n)
+        let mut chars = s.chars().enumerate().peekable();
+        let mut terms = Vec::with_capacity(s.len());
+        // the connectives that are still missing arguments: position, symbol, arity, missing
+        let mut open: Vec<(usize, char, usize, usize)> = Vec::new();
+        let mut complete = false;
+        while let Some((position, c)) = chars.next() {
+            if complete {
+                return Err(ParseError::ExtraSymbol { position, found: c });
+            }
+            let arity = if let Ok(v) = c.try_into() {
+                let arity = L::children(&v).len();
+                terms.push(Term::Term(v));
+                arity
+            } else {
+                if let Variables::Only(allowed) = variables {
+                    if !allowed.contains(c) {
+                        return Err(ParseError::UnknownSymbol { position, found: c });
+                    }
                 }
-            })
-            .collect::<Box<[_]>>()
-            .into();
-        if num_left == 0 {
-            Ok(f)
-        } else {
-            Err(ParseError::WrongArguments(num_left))
+                let mut name = String::from(c);
+                if c.is_alphabetic() {
+                    while let Some((_, d)) = chars.next_if(|(_, d)| d.is_ascii_digit()) {
+                        name.push(d);
+                    }
+                }
+                let n = vars_map.entry(name).or_insert_with(|| {
+                    let n = next_var;
+                    next_var += 1;
+                    n
+                });
+                terms.push(Term::Var(*n));
+                0
+            };
+            if arity > 0 {
+                open.push((position, c, arity, arity));
+                continue;
+            }
+            // a subformula is complete, which may complete the ones it is an argument of
+            loop {
+                let Some(last) = open.last_mut() else {
+                    complete = true;
+                    break;
+                };
+                last.3 -= 1;
+                if last.3 > 0 {
+                    break;
+                }
+                open.pop();
+            }
+        }
+        if let Some(&(position, connective, arity, missing)) = open.last() {
+            return Err(ParseError::MissingArguments {
+                position,
+                connective,
+                arity,
+                found: arity - missing,
+            });
         }
+        if !complete {
+            return Err(ParseError::Empty);
+        }
+        Ok(Normal(terms.into()))
     }
 }
 
 impl<L: Language> Normal<L> {
-    fn normalize_vars(&mut self) {
+    fn normalize_vars(terms: &mut [Term<L, ()>]) {
         let mut current_var = 0;
-        for i in 0..self.0.len() {
-            if let Term::Var(new_var) = self.0[i] {
+        for i in 0..terms.len() {
+            if let Term::Var(new_var) = terms[i] {
                 if current_var < new_var {
-                    for elem in &mut self.0[i..] {
+                    for elem in &mut terms[i..] {
                         if let Term::Var(x) = elem {
                             if *x == current_var {
                                 *x = new_var;
@@ -185,23 +319,67 @@ impl<L: Language> Normal<L> {
         self.0.len()
     }
 
-    pub fn from_arena(arena: &Arena<L>, idx: usize) -> Self {
-        fn inner<L: Language>(v: &mut Vec<Term<L, ()>>, arena: &Arena<L>, idx: usize) {
-            match &arena.0[idx] {
-                &Term::Var(x) => v.push(Term::Var(x)),
-                Term::Term(t) => {
-                    v.push(Term::Term(L::map(t, |_| ())));
-                    for &c in L::children(t) {
-                        inner(v, arena, c as usize);
-                    }
+    /// The maximal nesting of connectives that have arguments; variables and constants
+    /// have depth 0.
+    pub fn depth(&self) -> usize {
+        // number of arguments that are still missing for each enclosing connective
+        let mut open: Vec<usize> = Vec::new();
+        let mut depth = 0;
+        for t in self.0.iter() {
+            if let Term::Term(v) = t {
+                if !L::children(v).is_empty() {
+                    open.push(L::children(v).len());
+                    depth = std::cmp::max(depth, open.len());
+                    continue;
+                }
+            }
+            while let Some(missing) = open.last_mut() {
+                *missing -= 1;
+                if *missing > 0 {
+                    break;
                 }
+                open.pop();
             }
         }
+        depth
+    }
+
+    /// The number of distinct variables.
+    pub fn num_vars(&self) -> usize {
+        // variables are normalized to 0, 1, ... in order of appearance
+        self.0
+            .iter()
+            .filter_map(|t| match t {
+                &Term::Var(x) => Some(x as usize + 1),
+                Term::Term(_) => None,
+            })
+            .max()
+            .unwrap_or(0)
+    }
+
+    pub fn terms(&self) -> &[Term<L, ()>] {
+        &self.0
+    }
+
+    /// The antecedent `p` if the formula is an implication `p -> q`.
+    pub fn antecedent(&self) -> Option<&[Term<L, ()>]> {
+        let Term::Term(t) = &self.0[0] else {
+            return None;
+        };
+        L::match_implication(t)?;
+        Some(&self.0[1..=subterm_len(&self.0[1..])])
+    }
+
+    /// Whether `other` is a substitution instance of `self`.
+    pub fn subsumes(&self, other: &Self) -> bool {
+        matching(&self.0, &other.0).is_some()
+    }
+
+    pub fn from_arena(arena: &Arena<L>, idx: usize) -> Self {
         let mut v = Vec::new();
-        inner(&mut v, arena, idx);
-        let mut result = Self(v.into());
-        result.normalize_vars();
-        result
+        arena.read(idx, &mut v);
+        Self::normalize_vars(&mut v);
+        Self(v.into())
     }
 
     // recursively writes `val` into `arena`, adding `offset` to each index
@@ -266,19 +444,32 @@ impl<L: Language> Normal<L> {
 
 impl<L: Language, const N: usize> From<[Term<L, ()>; N]> for Normal<L> {
     fn from(value: [Term<L, ()>; N]) -> Self {
-        Self(Box::new(value))
+        Self(Arc::new(value))
     }
 }
 
 impl<L: Language> From<Box<[Term<L, ()>]>> for Normal<L> {
     fn from(value: Box<[Term<L, ()>]>) -> Self {
-        Self(value)
+        Self(value.into())
     }
 }
 
 pub struct Arena<L: Language>(Box<[Term<L, u16>]>);
 
 impl<L: Language> Arena<L> {
+    /// Appends the term at `idx` to `v`, keeping the variables as they are.
+    fn read(&self, idx: usize, v: &mut Vec<Term<L, ()>>) {
+        match &self.0[idx] {
+            &Term::Var(x) => v.push(Term::Var(x)),
+            Term::Term(t) => {
+                v.push(Term::Term(L::map(t, |_| ())));
+                for &c in L::children(t) {
+                    self.read(c as usize, v);
+                }
+            }
+        }
+    }
+
     fn substitute(&mut self, var: u16, term: &Term<L, u16>) {
         self.0.iter_mut().for_each(|t| match *t {
             Term::Var(v) if v == var => {
@@ -330,7 +521,53 @@ fn unify_many<L: Language>(arena: &mut Arena<L>, mut eqs: Vec<(u16, u16)>) -> bo
     true
 }
 
-pub fn modus_ponens<L: Language>(p: &Normal<L>, f: &Normal<L>) -> Option<Normal<L>> {
+/// Finds the substitution that turns the term `pattern` into the term at the start of
+/// `terms`, mapping each variable of `pattern` to a subterm of `terms`. Variables that do
+/// not occur in `pattern` are mapped to `None`.
+pub fn matching<'a, L: Language>(
+    pattern: &[Term<L, ()>],
+    terms: &'a [Term<L, ()>],
+) -> Option<Substitution<'a, L>> {
+    let mut substitution = Vec::new();
+    let mut j = 0;
+    for t in pattern {
+        match t {
+            &Term::Var(x) => {
+                let x = x as usize;
+                let subterm = &terms[j..j + subterm_len(&terms[j..])];
+                if substitution.len() <= x {
+                    substitution.resize(x + 1, None);
+                }
+                match substitution[x] {
+                    Some(s) if s != subterm => return None,
+                    Some(_) => {}
+                    None => substitution[x] = Some(subterm),
+                }
+                j += subterm.len();
+            }
+            Term::Term(v) => match &terms[j] {
+                Term::Term(w) if v == w => j += 1,
+                _ => return None,
+            },
+        }
+    }
+    Some(substitution)
+}
+
+fn max_var<L: Language>(f: &Normal<L>) -> u16 {
+    f.0.iter().fold(0, |acc, t| {
+        if let &Term::Var(x) = t {
+            std::cmp::max(acc, x)
+        } else {
+            acc
+        }
+    })
+}
+
+/// Writes `p` and `f` into an arena and unifies `p` with the antecedent of `f`.
+/// Returns the arena, where `p` starts at 0 and `f` at `p.len()`, and the position
+/// of the consequent of `f`.
+fn detach<L: Language>(p: &Normal<L>, f: &Normal<L>) -> Option<(Arena<L>, u16)> {
     let Term::Term(t) = &f.0[0] else {
         return None;
     };
@@ -340,13 +577,7 @@ pub fn modus_ponens<L: Language>(p: &Normal<L>, f: &Normal<L>) -> Option<Normal<
 
     let mut arena = Vec::with_capacity(p.len() + f.len());
 
-    let max_var = p.0.iter().fold(0, |acc, t| {
-        if let &Term::Var(x) = t {
-            std::cmp::max(acc, x)
-        } else {
-            acc
-        }
-    });
+    let max_var = max_var(p);
 
     let p = p.write_into(&mut arena, 0);
     let f = f.write_into(&mut arena, max_var + 1);
@@ -360,18 +591,84 @@ pub fn modus_ponens<L: Language>(p: &Normal<L>, f: &Normal<L>) -> Option<Normal<
 
     let mut arena = Arena(arena.into());
 
-    if unify_many(&mut arena, vec![(p, p1)]) {
-        Some(Normal::<L>::from_arena(&arena, q as usize))
-    } else {
-        None
+    unify_many(&mut arena, vec![(p, p1)]).then_some((arena, q))
+}
+
+pub fn modus_ponens<L: Language>(p: &Normal<L>, f: &Normal<L>) -> Option<Normal<L>> {
+    let (arena, q) = detach(p, f)?;
+    Some(Normal::<L>::from_arena(&arena, q as usize))
+}
+
+/// The substitutions that modus ponens applies to its premises. They map every variable
+/// of a premise to a term over the variables of the conclusion, followed by variables
+/// that do not occur in the conclusion.
+pub struct Unifier<L: Language> {
+    pub minor: Vec<Box<[Term<L, ()>]>>,
+    pub major: Vec<Box<[Term<L, ()>]>>,
+}
+
+/// Like [`modus_ponens`], but also returns the most general unifier that was applied.
+pub fn modus_ponens_with_unifier<L: Language>(
+    p: &Normal<L>,
+    f: &Normal<L>,
+) -> Option<(Normal<L>, Unifier<L>)> {
+    let (arena, q) = detach(p, f)?;
+    // the cell of the first occurrence of every variable of `terms`, which starts at `start`
+    let var_cells = |terms: &[Term<L, ()>], start: usize| {
+        let mut cells = Vec::new();
+        for (i, t) in terms.iter().enumerate() {
+            if let &Term::Var(x) = t {
+                if cells.len() <= x as usize {
+                    cells.resize(x as usize + 1, start + i);
+                }
+            }
+        }
+        cells
+    };
+    let read = |idx: usize| {
+        let mut v = Vec::new();
+        arena.read(idx, &mut v);
+        v
+    };
+
+    let mut conclusion = read(q as usize);
+    let mut minor: Vec<_> = var_cells(&p.0, 0).into_iter().map(read).collect();
+    let mut major: Vec<_> = var_cells(&f.0, p.len()).into_iter().map(read).collect();
+
+    // number the variables by their first occurrence, in the conclusion first
+    let mut names = HashMap::new();
+    for terms in std::iter::once(&mut conclusion)
+        .chain(&mut minor)
+        .chain(&mut major)
+    {
+        for t in terms.iter_mut() {
+            if let Term::Var(x) = t {
+                let next = u16::try_from(names.len()).unwrap();
+                *x = *names.entry(*x).or_insert(next);
+            }
+        }
     }
+
+    let unifier = Unifier {
+        minor: minor.into_iter().map(Vec::into_boxed_slice).collect(),
+        major: major.into_iter().map(Vec::into_boxed_slice).collect(),
+    };
+    Some((Normal(conclusion.into()), unifier))
 }
 
 #[cfg(test)]
 mod test {
-    use crate::formula::{langs, language::Arena};
+    use proptest::prelude::*;
 
-    use super::{Language, Normal};
+    use crate::{
+        context::Context,
+        formula::{langs, language::Arena},
+    };
+
+    use super::{
+        matching, modus_ponens, modus_ponens_with_unifier, var_name, Language, Normal, ParseError,
+        Term, Variables,
+    };
 
     fn test_conversion<L: Language>(f: &Normal<L>) {
         let mut arena = Vec::new();
@@ -392,4 +689,152 @@ mod test {
     fn test2() {
         test_conversion(&langs::ImpNeg::lukasiewicz_tarski()[0]);
     }
+
+    #[test]
+    fn measures() {
+        let f: Normal<langs::ImpNeg> = "CCNpNqCqp".parse().unwrap();
+        assert_eq!((f.len(), f.depth(), f.num_vars()), (9, 3, 2));
+        let f: Normal<langs::ImpFalse> = "CCCaFFa".parse().unwrap();
+        assert_eq!((f.len(), f.depth(), f.num_vars()), (7, 3, 1));
+        let f: Normal<langs::ImpFalse> = "F".parse().unwrap();
+        assert_eq!((f.depth(), f.num_vars()), (0, 0));
+    }
+
+    #[test]
+    fn unifier() {
+        let minor: Normal<langs::ImpNeg> = "CpCqp".parse().unwrap();
+        let major: Normal<langs::ImpNeg> = "CCpCqrCCpqCpr".parse().unwrap();
+        let (conclusion, unifier) = modus_ponens_with_unifier(&minor, &major).unwrap();
+        assert_eq!(Some(&conclusion), modus_ponens(&minor, &major).as_ref());
+        // the conclusion is CCpqCpp, with p := p, q := q in the minor premise
+        // and p := p, q := q, r := p in the major premise
+        let show = |terms: &[Box<[Term<langs::ImpNeg, ()>]>]| {
+            terms
+                .iter()
+                .map(|t| t.iter().map(ToString::to_string).collect::<String>())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(conclusion.to_string(), "CCpqCpp");
+        assert_eq!(show(&unifier.minor), ["p", "q"]);
+        assert_eq!(show(&unifier.major), ["p", "q", "p"]);
+
+        for minor in langs::ImpNeg::frege() {
+            for major in langs::ImpNeg::frege() {
+                let Some((conclusion, unifier)) = modus_ponens_with_unifier(&minor, &major) else {
+                    assert!(modus_ponens(&minor, &major).is_none());
+                    continue;
+                };
+                assert_eq!(Some(conclusion), modus_ponens(&minor, &major));
+                assert_eq!(unifier.minor.len(), minor.num_vars());
+                assert_eq!(unifier.major.len(), major.num_vars());
+            }
+        }
+    }
+
+    /// Formulas with up to 80 distinct variables, which need names with numbers
+    fn formulas() -> impl Strategy<Value = Normal<langs::ImpNeg>> {
+        fn connective(c: char) -> Term<langs::ImpNeg, ()> {
+            Term::Term(c.try_into().unwrap())
+        }
+        let leaf = (0u16..80).prop_map(|x| vec![Term::Var(x)]);
+        let terms = leaf.prop_recursive(8, 128, 2, |inner| {
+            prop_oneof![
+                inner.clone().prop_map(|a| {
+                    let mut terms = vec![connective('N')];
+                    terms.extend(a);
+                    terms
+                }),
+                (inner.clone(), inner).prop_map(|(a, b)| {
+                    let mut terms = vec![connective('C')];
+                    terms.extend(a);
+                    terms.extend(b);
+                    terms
+                }),
+            ]
+        });
+        terms.prop_map(|terms| {
+            let mut terms = terms;
+            Normal::normalize_vars(&mut terms);
+            Normal(terms.into())
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn display_round_trips(f in formulas()) {
+            prop_assert_eq!(f.to_string().parse::<Normal<langs::ImpNeg>>().unwrap(), f);
+        }
+    }
+
+    #[test]
+    fn round_trips_derived_formulas() {
+        assert_eq!(var_name(0), "p");
+        assert_eq!(var_name(11), "a");
+        assert_eq!(var_name(25), "o");
+        assert_eq!(var_name(26), "p1");
+        assert_eq!(var_name(27), "p2");
+
+        let axioms = langs::ImpNeg::lukasiewicz3();
+        let mut context = Context::new(&axioms);
+        for _ in 0..3 {
+            context.step(&(|_, _, _| ()));
+        }
+        for f in context.entries.keys() {
+            assert_eq!(&f.to_string().parse::<Normal<_>>().unwrap(), f);
+        }
+    }
+
+    #[test]
+    fn parse_errors() {
+        type F = Normal<langs::ImpNeg>;
+        let strict = |s: &str| F::parse_with(s, Variables::Only("pqr"));
+        assert_eq!(strict("CpCqp1"), Ok("CpCqs".parse().unwrap()));
+        assert_eq!(strict(""), Err(ParseError::Empty));
+        assert_eq!(
+            strict("CpCq"),
+            Err(ParseError::MissingArguments {
+                position: 2,
+                connective: 'C',
+                arity: 2,
+                found: 1
+            })
+        );
+        assert_eq!(
+            strict("CpqN"),
+            Err(ParseError::ExtraSymbol {
+                position: 3,
+                found: 'N'
+            })
+        );
+        assert_eq!(
+            strict("Cp q"),
+            Err(ParseError::UnknownSymbol {
+                position: 2,
+                found: ' '
+            })
+        );
+        assert_eq!(
+            strict("CpX"),
+            Err(ParseError::UnknownSymbol {
+                position: 2,
+                found: 'X'
+            })
+        );
+        // without an alphabet, every other character is a variable
+        assert_eq!("CpX".parse::<F>(), Ok("Cpq".parse().unwrap()));
+    }
+
+    #[test]
+    fn matches_instances() {
+        let pattern: Normal<langs::ImpNeg> = "CpCqp".parse().unwrap();
+        let instance: Normal<langs::ImpNeg> = "CNpCqNp".parse().unwrap();
+        let substitution = matching(pattern.terms(), instance.terms()).unwrap();
+        assert_eq!(
+            substitution,
+            [Some(&instance.terms()[1..3]), Some(&instance.terms()[4..5])]
+        );
+        assert!(matching(instance.terms(), pattern.terms()).is_none());
+        let f: Normal<langs::ImpNeg> = "CNpCqNq".parse().unwrap();
+        assert!(matching(pattern.terms(), f.terms()).is_none());
+    }
 }
@@ -1,2 +1,4 @@
+pub mod index;
+pub mod infix;
 pub mod langs;
 pub mod language;
@@ -0,0 +1,214 @@
+//! Proofs where substitution is explicit, as found in textbooks: every line is an instance
+//! of an axiom under a given substitution, or follows by modus ponens from two earlier
+//! lines `A` and `CAB` whose formulas match exactly.
+
+use ahash::HashMap;
+
+use crate::{
+    context::{Proof, Source},
+    formula::language::{modus_ponens_with_unifier, Language, Normal, Term},
+};
+
+type Formula<L> = Box<[Term<L, ()>]>;
+
+pub struct HilbertProof<L: Language> {
+    pub lines: Vec<Line<L>>,
+}
+
+pub struct Line<L: Language> {
+    /// The formula, whose variables are not normalized
+    pub formula: Formula<L>,
+    pub justification: Justification<L>,
+}
+
+pub enum Justification<L: Language> {
+    /// The axiom with the given index and the terms its variables are replaced with
+    Axiom {
+        index: usize,
+        substitution: Vec<Formula<L>>,
+    },
+    /// Modus ponens from the lines of the minor and the major premise, counting from 1
+    MP(usize, usize),
+}
+
+/// Replaces every variable `x` in `terms` by `substitution[x]`.
+fn substitute<L: Language>(terms: &[Term<L, ()>], substitution: &[Formula<L>]) -> Formula<L> {
+    let mut result = Vec::with_capacity(terms.len());
+    for t in terms {
+        match t {
+            &Term::Var(x) => result.extend(substitution[x as usize].iter().cloned()),
+            Term::Term(_) => result.push(t.clone()),
+        }
+    }
+    result.into()
+}
+
+fn max_var<L: Language>(terms: &[Formula<L>]) -> Option<u16> {
+    terms
+        .iter()
+        .flat_map(|t| t.iter())
+        .filter_map(|t| match t {
+            &Term::Var(x) => Some(x),
+            Term::Term(_) => None,
+        })
+        .max()
+}
+
+struct Expansion<'a, L: Language> {
+    proof: &'a Proof<L>,
+    lines: Vec<Line<L>>,
+    /// The line of every formula that was already written out
+    known: HashMap<Formula<L>, usize>,
+}
+
+impl<L: Language> Expansion<'_, L> {
+    /// Writes out the instance of the proof step `step` under `substitution` and returns its line.
+    fn expand(&mut self, step: usize, mut substitution: Vec<Formula<L>>) -> usize {
+        let step = &self.proof.steps[step];
+        let formula = substitute(step.formula.terms(), &substitution);
+        if let Some(&line) = self.known.get(&formula) {
+            return line;
+        }
+        let justification = match step.source {
+            Source::Axiom => Justification::Axiom {
+                index: step.index,
+                substitution,
+            },
+            Source::MP(minor, major) => {
+                let (minor, major) = (minor - 1, major - 1);
+                let (conclusion, unifier) = modus_ponens_with_unifier(
+                    &self.proof.steps[minor].formula,
+                    &self.proof.steps[major].formula,
+                )
+                .expect("proof steps follow by modus ponens");
+                assert_eq!(conclusion, step.formula);
+                // variables that only occur in the premises stay variables, distinct from the others
+                let fresh = max_var(&substitution).map_or(0, |x| x + 1);
+                let vars = substitution.len();
+                let extra = max_var(&unifier.minor)
+                    .into_iter()
+                    .chain(max_var(&unifier.major))
+                    .max()
+                    .map_or(0, |x| (x as usize + 1).saturating_sub(vars));
+                for i in 0..extra {
+                    let x = fresh + u16::try_from(i).unwrap();
+                    substitution.push([Term::Var(x)].into());
+                }
+                let instances = |premise: &[Formula<L>]| {
+                    premise
+                        .iter()
+                        .map(|t| substitute(t, &substitution))
+                        .collect::<Vec<_>>()
+                };
+                let (minor_instance, major_instance) =
+                    (instances(&unifier.minor), instances(&unifier.major));
+                let minor = self.expand(minor, minor_instance);
+                let major = self.expand(major, major_instance);
+                Justification::MP(minor, major)
+            }
+        };
+        self.lines.push(Line {
+            formula: formula.clone(),
+            justification,
+        });
+        self.known.insert(formula, self.lines.len());
+        self.lines.len()
+    }
+}
+
+impl<L: Language> HilbertProof<L> {
+    /// Expands a condensed detachment proof, writing out the instances of the axioms and
+    /// premises that every detachment uses. A step may appear several times, with
+    /// different instances.
+    pub fn expand(proof: &Proof<L>) -> Self {
+        let mut expansion = Expansion {
+            proof,
+            lines: Vec::new(),
+            known: HashMap::default(),
+        };
+        let last = proof.steps.last().expect("proofs are not empty");
+        let identity = (0..last.formula.num_vars())
+            .map(|x| [Term::Var(u16::try_from(x).unwrap())].into())
+            .collect();
+        expansion.expand(proof.steps.len() - 1, identity);
+        Self {
+            lines: expansion.lines,
+        }
+    }
+
+    /// Checks every line by comparing formulas, and returns the first line, counting from 1,
+    /// that is not justified. The axiom lines refer to `axioms` by their index.
+    pub fn verify(&self, axioms: &[Normal<L>]) -> Result<(), usize> {
+        for (n, line) in self.lines.iter().enumerate() {
+            let valid = match &line.justification {
+                Justification::Axiom {
+                    index,
+                    substitution,
+                } => axioms.get(*index).is_some_and(|axiom| {
+                    axiom.num_vars() <= substitution.len()
+                        && substitute(axiom.terms(), substitution) == line.formula
+                }),
+                &Justification::MP(minor, major) => {
+                    let premise = |i: usize| {
+                        i.checked_sub(1)
+                            .filter(|&i| i < n)
+                            .map(|i| &self.lines[i].formula)
+                    };
+                    premise(minor)
+                        .zip(premise(major))
+                        .is_some_and(|(minor, major)| is_implication(major, minor, &line.formula))
+                }
+            };
+            if !valid {
+                return Err(n + 1);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Whether `f` is the implication from `p` to `q`.
+fn is_implication<L: Language>(f: &[Term<L, ()>], p: &[Term<L, ()>], q: &[Term<L, ()>]) -> bool {
+    let Some((Term::Term(t), rest)) = f.split_first() else {
+        return false;
+    };
+    L::match_implication(t).is_some()
+        && rest.len() == p.len() + q.len()
+        && rest.starts_with(p)
+        && rest.ends_with(q)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        context::{Context, SourceChoice},
+        formula::langs::ImpNeg,
+    };
+
+    use super::{HilbertProof, Justification};
+
+    #[test]
+    fn expands_proofs() {
+        let axioms = ImpNeg::lukasiewicz3();
+        let mut context = Context::new(&axioms);
+        for _ in 0..3 {
+            context.step(&(|_, _, _| ()));
+        }
+        for f in context.entries.keys() {
+            let proof = context.proof_of(f, SourceChoice::Smallest).unwrap();
+            let expanded = HilbertProof::expand(&proof);
+            assert_eq!(expanded.verify(&axioms), Ok(()));
+            assert_eq!(*expanded.lines.last().unwrap().formula, *f.terms());
+        }
+
+        let identity = context.proof_of(&"Cpp".parse().unwrap(), SourceChoice::Smallest);
+        let mut expanded = HilbertProof::expand(&identity.unwrap());
+        // CpCqp, CpCCqpp, CCpCCqppCCpCqpCpp, CCpCqpCpp and Cpp
+        assert_eq!(expanded.lines.len(), 5);
+        let Justification::Axiom { substitution, .. } = &mut expanded.lines[0].justification else {
+            panic!("the first line is an axiom");
+        };
+        substitution.swap(0, 1);
+        assert_eq!(expanded.verify(&axioms), Err(1));
+    }
+}
@@ -0,0 +1,143 @@
+use std::fmt::Display;
+
+use crate::{
+    dproof::DParseError,
+    formula::language::{Language, Normal, ParseError, Variables},
+};
+
+/// A formula read from a file, together with the name it was given there.
+pub type Named<L> = (Option<String>, Normal<L>);
+
+#[derive(Debug)]
+pub struct InputError {
+    pub line: usize,
+    pub kind: InputErrorKind,
+}
+
+#[derive(Debug)]
+pub enum InputErrorKind {
+    Parse(ParseError),
+    EmptyName,
+    DuplicateName(String),
+    /// The formula is the same as the one on the given line, up to renaming variables
+    DuplicateFormula(usize),
+    InvalidNumber(String),
+    UnknownSymbol(String),
+    UnknownSetting(String),
+    InvalidProof(DParseError),
+    MissingProof,
+}
+
+impl Display for InputError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}: ", self.line)?;
+        match &self.kind {
+            InputErrorKind::Parse(e) => write!(f, "{e}"),
+            InputErrorKind::EmptyName => write!(f, "missing name before `:`"),
+            InputErrorKind::DuplicateName(name) => write!(f, "name `{name}` is used twice"),
+            InputErrorKind::DuplicateFormula(line) => {
+                write!(f, "the formula is the same as on line {line}")
+            }
+            InputErrorKind::InvalidNumber(n) => write!(f, "`{n}` is not a valid number"),
+            InputErrorKind::UnknownSymbol(c) => write!(f, "`{c}` is not a connective"),
+            InputErrorKind::UnknownSetting(s) => write!(f, "unknown setting `{s}`"),
+            InputErrorKind::InvalidProof(e) => write!(f, "invalid proof, {e}"),
+            InputErrorKind::MissingProof => write!(f, "expected a name, a formula and a proof"),
+        }
+    }
+}
+
+/// Iterates over the non-empty lines of `text` with comments (starting at `#`) removed,
+/// together with their 1-based line numbers.
+pub fn lines(text: &str) -> impl Iterator<Item = (usize, &str)> {
+    text.lines().enumerate().filter_map(|(i, line)| {
+        let line = line
+            .split_once('#')
+            .map_or(line, |(before, _)| before)
+            .trim();
+        (!line.is_empty()).then_some((i + 1, line))
+    })
+}
+
+/// Reads one formula in Polish notation per line, optionally preceded by a name and a colon:
+///
+/// ```text
+/// # Łukasiewicz's third system
+/// ax1: CpCqp
+/// ax2: CCpCqrCCpqCpr
+/// CCNpNqCqp   # unnamed
+/// ```
+pub fn read_formulas<L: Language>(
+    text: &str,
+    variables: Variables<'_>,
+) -> Result<Vec<Named<L>>, InputError>
+where
+    L::Variant<()>: TryFrom<char>,
+{
+    let mut formulas: Vec<Named<L>> = Vec::new();
+    let mut lines_read = Vec::new();
+    for (line, content) in lines(text) {
+        let error = |kind| InputError { line, kind };
+        let (name, formula) = match content.split_once(':') {
+            Some((name, formula)) => {
+                let name = name.trim();
+                if name.is_empty() {
+                    return Err(error(InputErrorKind::EmptyName));
+                }
+                if formulas.iter().any(|(n, _)| n.as_deref() == Some(name)) {
+                    return Err(error(InputErrorKind::DuplicateName(name.to_owned())));
+                }
+                (Some(name.to_owned()), formula.trim())
+            }
+            None => (None, content),
+        };
+        let formula =
+            Normal::parse_with(formula, variables).map_err(|e| error(InputErrorKind::Parse(e)))?;
+        if let Some(i) = formulas.iter().position(|(_, f)| *f == formula) {
+            return Err(error(InputErrorKind::DuplicateFormula(lines_read[i])));
+        }
+        formulas.push((name, formula));
+        lines_read.push(line);
+    }
+    Ok(formulas)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::formula::{langs::ImpNeg, language::Variables};
+
+    use super::{read_formulas, InputErrorKind};
+
+    #[test]
+    fn names_and_comments() {
+        let text = "# comment\nax1: CpCqp\n\nCCNpNqCqp # unnamed\n";
+        let formulas = read_formulas::<ImpNeg>(text, Variables::Any).unwrap();
+        assert_eq!(formulas.len(), 2);
+        assert_eq!(formulas[0].0.as_deref(), Some("ax1"));
+        assert_eq!(formulas[0].1, "CpCqp".parse().unwrap());
+        assert_eq!(formulas[1].0, None);
+    }
+
+    #[test]
+    fn errors() {
+        let err = read_formulas::<ImpNeg>("CpCqp\nCCp\n", Variables::Any).unwrap_err();
+        assert_eq!(err.line, 2);
+        assert!(matches!(err.kind, InputErrorKind::Parse(_)));
+
+        let err = read_formulas::<ImpNeg>("CpCqp\nCpCq p\n", Variables::Only("pq")).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "line 2: ` ` at position 4 is neither a connective nor a variable"
+        );
+
+        let err = read_formulas::<ImpNeg>("a: Cpp\na: CpCqp\n", Variables::Any).unwrap_err();
+        assert!(matches!(err.kind, InputErrorKind::DuplicateName(_)));
+
+        let err = read_formulas::<ImpNeg>("a1: CpCqp\n\na1b: CqCpq\n", Variables::Any).unwrap_err();
+        assert_eq!(err.line, 3);
+        assert_eq!(
+            err.to_string(),
+            "line 3: the formula is the same as on line 1"
+        );
+    }
+}
@@ -2,24 +2,139 @@
 #![allow(clippy::question_mark, clippy::comparison_chain)]
 
 use std::{
+    fmt::Display,
     io::{self, Write},
     ops::AddAssign,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
 };
 
 use ahash::{HashMap, HashMapExt};
-use clap::Parser;
+use clap::{error::ErrorKind, CommandFactory, Parser, Subcommand, ValueEnum};
 
+mod check;
 mod context;
+mod dproof;
 mod formula;
-use formula::langs;
+mod hilbert;
+mod input;
+mod pmproofs;
+mod weight;
+use dproof::DProof;
+use formula::{
+    infix::{parse_infix, Infix, Infixed},
+    langs::{self, Systems},
+    language::{modus_ponens_with_unifier, var_name, Language, Normal, Term, Variables},
+};
+use hilbert::{HilbertProof, Justification};
+use input::Named;
+use weight::Weights;
 
-use context::{Context, Source};
+use context::{resident_memory, Budget, Context, Limits, Proof, Source, SourceChoice};
 use itertools::Itertools;
 use rayon::iter::{ParallelDrainFull, ParallelExtend, ParallelIterator};
 
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum Lang {
+    /// Implication and negation (`C`, `N`)
+    ImpNeg,
+    /// Implication and falsum (`C`, `F`)
+    ImpFalse,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum Notation {
+    /// Polish notation, such as `CCpqCNqNp`
+    Polish,
+    /// Infix notation, such as `(p -> q) -> -q -> -p`
+    Infix,
+}
+
+impl Notation {
+    fn show<L: Infix>(self, terms: &[Term<L, ()>]) -> String
+    where
+        L::Variant<()>: Display,
+    {
+        match self {
+            Self::Polish => terms.iter().join(""),
+            Self::Infix => Infixed(terms).to_string(),
+        }
+    }
+
+    /// Shows the terms that are substituted for the variables `p`, `q` and so on.
+    fn show_substitution<'a, L: Infix>(
+        self,
+        substitution: impl IntoIterator<Item = (usize, &'a [Term<L, ()>])>,
+    ) -> String
+    where
+        L::Variant<()>: Display,
+    {
+        substitution
+            .into_iter()
+            .map(|(x, t)| {
+                let x = var_name(u16::try_from(x).unwrap());
+                format!("{x} := {}", self.show(t))
+            })
+            .join(", ")
+    }
+
+    /// Parses a formula given on the command line, and exits with a message that
+    /// points at the error if it is invalid.
+    fn parse<L: Infix>(self, s: &str, variables: Variables<'_>) -> Normal<L>
+    where
+        L::Variant<()>: TryFrom<char>,
+    {
+        let result = match self {
+            Self::Polish => {
+                Normal::parse_with(s, variables).map_err(|e| (e.position(), e.to_string()))
+            }
+            Self::Infix => parse_infix(s).map_err(|e| (Some(e.position), e.to_string())),
+        };
+        result.unwrap_or_else(|(position, e)| {
+            // the end of the formula if it is incomplete
+            let position = position.unwrap_or(s.chars().count());
+            let marker = format!("{}^", " ".repeat(position));
+            fail(
+                ErrorKind::InvalidValue,
+                format!("invalid formula, {e}\n\n    {s}\n    {marker}"),
+            )
+        })
+    }
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum Proofs {
+    /// The smallest known proof
+    Smallest,
+    /// The derivation every step was added with, the one with the lowest premise
+    /// indices among those of the same iteration
+    First,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Evaluate a proof in D-notation, such as `DD211`, against the axioms and print the theorem
+    EvalProof { proof: String },
+    /// Check a collection of proofs in D-notation against the axioms, one
+    /// `name formula proof` per line
+    ImportProofs {
+        file: String,
+        /// Also search for every theorem and compare the sizes of the proofs
+        #[arg(long)]
+        compare: bool,
+    },
+}
+
 #[derive(Parser, Debug)]
 #[command(version, about, long_about = None)]
+#[allow(clippy::struct_excessive_bools)]
 struct Args {
+    #[command(subcommand)]
+    command: Option<Command>,
+
     /// Number of iterations
     #[arg(short, long, default_value_t = 5)]
     iterations: u32,
@@ -27,119 +142,789 @@ struct Args {
     #[arg(short, long)]
     search: Option<String>,
 
+    /// Notation of `--search` and of the printed formulas; files are always read in
+    /// Polish notation
+    #[arg(long, value_enum, default_value_t = Notation::Polish, global = true)]
+    notation: Notation,
+
+    /// The letters that variables in Polish notation may start with; any other
+    /// character that is not a connective is an error
+    #[arg(long, default_value = "abcdefghijklmnopqrstuvwxyz", global = true)]
+    variables: String,
+
+    /// Check every printed proof with a simple proof checker that does not rely
+    /// on the unification used in the search
+    #[arg(long)]
+    check: bool,
+
+    /// Also print every proof with explicit substitution instances of the axioms
+    /// and premises, and verify it line by line
     #[arg(long)]
+    expand: bool,
+
+    /// Which derivation of each step to use when printing proofs
+    #[arg(long, value_enum, default_value_t = Proofs::Smallest)]
+    proofs: Proofs,
+
+    /// Search for every formula in a file, in the same format as `--axioms`,
+    /// and stop once all of them are found
+    #[arg(long)]
+    targets: Option<String>,
+
+    /// Write how many formulas of each length the next iteration would derive to a file;
+    /// only for level saturation
+    #[arg(long, conflicts_with = "given")]
     stats: Option<String>,
+
+    /// Save the search to a file after every iteration, to continue it with `--resume`
+    #[arg(long, conflicts_with = "given")]
+    checkpoint: Option<String>,
+
+    /// Continue a search saved with `--checkpoint`, with the same language and axioms
+    #[arg(long, conflicts_with = "given")]
+    resume: Option<String>,
+
+    /// Language of the formulas
+    #[arg(short, long, value_enum, default_value_t = Lang::ImpNeg, global = true)]
+    language: Lang,
+
+    /// Name of the built-in axiom system [default: `meredith` for imp-neg, `church`
+    /// for imp-false]
+    #[arg(long, global = true)]
+    system: Option<String>,
+
+    /// Read the axioms from a file instead, one formula per line,
+    /// optionally preceded by `name:`
+    #[arg(long, conflicts_with = "system", global = true)]
+    axioms: Option<String>,
+
+    /// Drop derived formulas that are instances of an earlier one and
+    /// retire earlier ones that are instances of a new one
+    #[arg(long)]
+    subsumption: bool,
+
+    /// Discard derived formulas with more symbols
+    #[arg(long)]
+    max_len: Option<usize>,
+
+    /// Discard derived formulas with deeper nested connectives
+    #[arg(long)]
+    max_depth: Option<usize>,
+
+    /// Discard derived formulas with more distinct variables
+    #[arg(long)]
+    max_vars: Option<usize>,
+
+    /// Discard derived formulas with a larger weight
+    #[arg(long)]
+    max_weight: Option<usize>,
+
+    /// Stop the search after this much time, such as `90s`, `30m` or `2h`; an iteration
+    /// of level saturation that runs out of time is discarded
+    #[arg(long, value_parser = parse_duration)]
+    max_time: Option<Duration>,
+
+    /// Stop the search before it has more entries
+    #[arg(long)]
+    max_entries: Option<usize>,
+
+    /// Stop the search once the process uses more memory, such as `512M` or `8G`
+    #[arg(long, value_parser = parse_memory)]
+    max_memory: Option<usize>,
+
+    /// Read the weights of symbols and patterns from a file
+    #[arg(long)]
+    weights: Option<String>,
+
+    /// Read hints from a file, in the same format as `--axioms`; derived formulas
+    /// that subsume a hint get a greatly reduced weight
+    #[arg(long)]
+    hints: Option<String>,
+
+    /// Search best-first with a given-clause loop instead of level saturation;
+    /// every iteration then processes a single formula
+    #[arg(long)]
+    given: bool,
+
+    /// Number of formulas picked by weight for every formula picked by age
+    #[arg(long, default_value_t = 4, requires = "given")]
+    pick_ratio: usize,
+
+    /// Print every built-in axiom system with its axioms and exit
+    #[arg(long)]
+    list_systems: bool,
+}
+
+/// Parses a number of seconds, minutes or hours, such as `90s`, `30m` or `2h`.
+fn parse_duration(s: &str) -> Result<Duration, String> {
+    let (number, unit) = s.split_at(s.find(|c: char| c.is_alphabetic()).unwrap_or(s.len()));
+    let seconds = match unit {
+        "" | "s" => 1.0,
+        "m" => 60.0,
+        "h" => 3600.0,
+        _ => return Err(format!("unknown unit `{unit}`, expected `s`, `m` or `h`")),
+    };
+    let number: f64 = number.parse().map_err(|e| format!("{e}"))?;
+    Duration::try_from_secs_f64(number * seconds).map_err(|e| format!("{e}"))
+}
+
+/// Parses a number of bytes, optionally with a binary `K`, `M` or `G` suffix.
+fn parse_memory(s: &str) -> Result<usize, String> {
+    let (number, unit) = s.split_at(s.find(|c: char| c.is_alphabetic()).unwrap_or(s.len()));
+    let shift = match unit {
+        "" => 0,
+        "K" => 10,
+        "M" => 20,
+        "G" => 30,
+        _ => return Err(format!("unknown unit `{unit}`, expected `K`, `M` or `G`")),
+    };
+    let number: usize = number.parse().map_err(|e| format!("{e}"))?;
+    number
+        .checked_mul(1 << shift)
+        .ok_or_else(|| "too large".to_owned())
+}
+
+impl Args {
+    fn variables(&self) -> Variables<'_> {
+        Variables::Only(&self.variables)
+    }
 }
 
 fn main() -> io::Result<()> {
     let args = Args::parse();
 
-    let search = args.search.map(|f| f.parse().unwrap());
+    if args.list_systems {
+        list_systems::<langs::ImpNeg>(Lang::ImpNeg, args.notation);
+        list_systems::<langs::ImpFalse>(Lang::ImpFalse, args.notation);
+        return Ok(());
+    }
 
-    let mut context = Context::new(&langs::ImpNeg::meredith());
-    let runs = args.iterations;
+    match args.language {
+        Lang::ImpNeg => dispatch::<langs::ImpNeg>(args),
+        Lang::ImpFalse => dispatch::<langs::ImpFalse>(args),
+    }
+}
+
+fn dispatch<L: Systems + Infix>(args: Args) -> io::Result<()>
+where
+    L::Variant<()>: Display + TryFrom<char>,
+{
+    match &args.command {
+        Some(Command::EvalProof { proof }) => {
+            eval_proof::<L>(&args, proof);
+            Ok(())
+        }
+        Some(Command::ImportProofs { file, compare }) => {
+            import_proofs::<L>(&args, file, *compare);
+            Ok(())
+        }
+        None => run::<L>(args),
+    }
+}
 
-    let mut found = None;
+fn eval_proof<L: Systems + Infix>(args: &Args, proof: &str)
+where
+    L::Variant<()>: Display + TryFrom<char>,
+{
+    let proof: DProof = proof
+        .parse()
+        .unwrap_or_else(|e| fail(ErrorKind::InvalidValue, format!("invalid proof, {e}")));
+    let axioms: Vec<_> = load_axioms::<L>(args).into_iter().map(|(_, f)| f).collect();
+    match proof.evaluate(&axioms) {
+        Ok(theorem) => println!("{}", args.notation.show(theorem.terms())),
+        Err(e) => fail(ErrorKind::InvalidValue, format!("invalid proof, {e}")),
+    }
+}
 
-    for run in 0..runs {
-        context.step(&(|_, _, _| ()));
+fn fail(kind: ErrorKind, message: impl Display) -> ! {
+    Args::command().error(kind, message).exit()
+}
 
-        let num_entries = context.entries.len();
+fn read_file(path: &str) -> String {
+    std::fs::read_to_string(path)
+        .unwrap_or_else(|e| fail(ErrorKind::Io, format!("cannot read `{path}`: {e}")))
+}
 
-        println!("run {run}, now {num_entries} entries");
+fn import_proofs<L: Systems + Infix>(args: &Args, path: &str, compare: bool)
+where
+    L::Variant<()>: Display + TryFrom<char>,
+{
+    let axioms = load_axioms::<L>(args);
+    let formulas: Vec<_> = axioms.iter().map(|(_, f)| f.clone()).collect();
+    let imported = pmproofs::read_proofs(&read_file(path), &formulas, args.variables())
+        .unwrap_or_else(|e| fail(ErrorKind::InvalidValue, format!("in `{path}`, {e}")));
+    let show = |f: &Normal<L>| args.notation.show(f.terms());
+    for i in &imported {
+        match &i.result {
+            Ok((theorem, size)) if *theorem == i.expected => {
+                println!("{}: {} verified, {size} steps", i.name, show(theorem));
+            }
+            Ok((theorem, _)) => println!(
+                "{}: proves {} instead of {}",
+                i.name,
+                show(theorem),
+                show(&i.expected)
+            ),
+            Err(e) => println!("{}: {e}", i.name),
+        }
+    }
+    let verified = imported.iter().filter(|i| i.verified()).count();
+    println!("verified {verified} of {} proofs", imported.len());
 
-        if let Some(f) = &search {
-            if let Some(formula) = context.entries.get(f) {
-                println!("Found formula ({f}) after {run} iterations");
-                found = Some(formula.clone());
-                break;
-                // if let Source::MP(s1, s2) = found.source {
-                //     let prev = context.entries.iter().filter(|(f,m)| m.index == s1 || m.index == s2).collect();
-                // }
+    if compare {
+        let mut context = configure(args, Context::with_names(&axioms));
+        for _ in 0..args.iterations {
+            if args.given {
+                if context.given_step(&(|_, _, _| ())).is_none() {
+                    break;
+                }
             } else {
-                println!("Formula ({f}) not found in iteration {run}");
+                context.step(&(|_, _, _| ()));
+            }
+            if imported
+                .iter()
+                .all(|i| context.find_subsuming(&i.expected).is_some())
+            {
+                break;
+            }
+        }
+        for i in &imported {
+            let imported_size = match &i.result {
+                Ok((_, size)) if i.verified() => size.to_string(),
+                _ => "no valid".to_owned(),
+            };
+            match context.find_subsuming(&i.expected) {
+                Some((_, meta, _)) => println!(
+                    "{}: {imported_size} steps imported, {} steps found",
+                    i.name, meta.size
+                ),
+                None => println!("{}: {imported_size} steps imported, not found", i.name),
             }
         }
+    }
+}
 
-        println!("run {run} complete");
+fn read_named<L: Language>(args: &Args, path: &str) -> Vec<Named<L>>
+where
+    L::Variant<()>: TryFrom<char>,
+{
+    input::read_formulas(&read_file(path), args.variables())
+        .unwrap_or_else(|e| fail(ErrorKind::InvalidValue, format!("in `{path}`, {e}")))
+}
+
+fn list_systems<L: Systems + Infix>(lang: Lang, notation: Notation)
+where
+    L::Variant<()>: Display,
+{
+    println!("{}:", lang.to_possible_value().unwrap().get_name());
+    for (name, axioms) in L::SYSTEMS {
+        println!("  {name}");
+        for axiom in axioms() {
+            println!("    {}", notation.show(axiom.terms()));
+        }
     }
+}
 
-    if let Some(search) = search {
-        if let Some(found) = found {
-            let mut derivation = HashMap::new();
-            derivation.insert(found.index, (found.clone(), search));
+fn load_axioms<L: Systems>(args: &Args) -> Vec<Named<L>>
+where
+    L::Variant<()>: TryFrom<char>,
+{
+    let system = args.system.as_deref().unwrap_or(L::SYSTEMS[0].0);
+    if let Some(path) = &args.axioms {
+        read_named(args, path)
+    } else if let Some(axioms) = L::system(system) {
+        axioms.into_iter().map(|f| (None, f)).collect()
+    } else {
+        let available = L::SYSTEMS.iter().map(|(name, _)| name).join(", ");
+        fail(
+            ErrorKind::InvalidValue,
+            format!(
+                "unknown axiom system `{system}` for language {} (available: {available})",
+                language_name(args),
+            ),
+        );
+    }
+}
 
-            let mut to_find: Vec<_> = found
-                .sources
-                .iter()
-                .filter_map(|s| {
-                    if let &Source::MP(a, b) = s {
-                        Some([a, b].into_iter())
-                    } else {
-                        None
-                    }
-                })
-                .flatten()
-                .collect();
-
-            while !to_find.is_empty() {
-                let new = context
-                    .entries
-                    .iter()
-                    .filter(|(_, m)| to_find.contains(&m.index))
-                    .flat_map(|(e, m)| {
-                        derivation.insert(m.index, (m.clone(), e.clone()));
-                        m.sources
-                            .iter()
-                            .filter_map(|s| {
-                                if let &Source::MP(a, b) = s {
-                                    Some([a, b].into_iter())
-                                } else {
-                                    None
-                                }
-                            })
-                            .flatten()
-                    })
-                    .collect();
-
-                to_find = new;
+/// Applies the settings to a new or resumed context.
+fn configure<L: Language>(args: &Args, mut context: Context<L>) -> Context<L>
+where
+    L::Variant<()>: TryFrom<char>,
+{
+    if args.subsumption {
+        context.enable_subsumption();
+    }
+    context.set_limits(Limits {
+        len: args.max_len,
+        depth: args.max_depth,
+        vars: args.max_vars,
+        weight: args.max_weight,
+    });
+    if let Some(path) = &args.weights {
+        let text = read_file(path);
+        let weights = Weights::read(&text, args.variables())
+            .unwrap_or_else(|e| fail(ErrorKind::InvalidValue, format!("in `{path}`, {e}")));
+        context.set_weights(weights);
+    }
+    if let Some(path) = &args.hints {
+        context.set_hints(read_named(args, path));
+    }
+    if args.given {
+        context.enable_given_clause(args.pick_ratio);
+    }
+    if args.max_memory.is_some() && resident_memory().is_none() {
+        fail(
+            ErrorKind::InvalidValue,
+            "--max-memory is not supported on this platform",
+        );
+    }
+    context.set_budget(Budget {
+        time: args.max_time,
+        entries: args.max_entries,
+        memory: args.max_memory,
+    });
+    context
+}
+
+fn run<L: Systems + Infix>(args: Args) -> io::Result<()>
+where
+    L::Variant<()>: Display + TryFrom<char>,
+{
+    let axioms = load_axioms::<L>(&args);
+
+    let mut targets: Vec<Target<L>> = args
+        .search
+        .iter()
+        .map(|f| (None, args.notation.parse(f, args.variables())))
+        .chain(args.targets.iter().flat_map(|path| read_named(&args, path)))
+        .map(|(name, formula)| Target {
+            name,
+            formula,
+            found: None,
+        })
+        .collect();
+
+    let context = match &args.resume {
+        Some(path) => load_checkpoint(&args, &axioms, path),
+        None => Context::with_names(&axioms),
+    };
+    let mut context = configure(&args, context);
+    print_matched_hints(&context, args.notation);
+    // the targets may already be among the axioms or the resumed entries
+    let found_all = !targets.is_empty() && find_targets(&context, &mut targets, args.notation) == 0;
+    let runs = if found_all { 0 } else { args.iterations };
+    let interrupts = watch_interrupts();
+    context.set_interrupt(interrupts.abort.clone());
+
+    // a resumed search continues with the next iteration
+    for run in u32::try_from(context.generation()).unwrap()..runs {
+        if let Some(exceeded) = context.exceeded_budget() {
+            println!("stopping before run {run}, the {exceeded} budget is exhausted");
+            break;
+        }
+        let stats = if args.given {
+            let Some(step) = context.given_step(&(|_, _, _| ())) else {
+                println!("No formulas left to pick");
+                break;
+            };
+            if !step.stats.interrupted {
+                let given = args.notation.show(step.given.terms());
+                println!("given: {given} (weight {})", step.weight);
             }
+            step.stats
+        } else {
+            context.step(&(|_, _, _| ()))
+        };
+        if stats.interrupted {
+            match stats.exceeded {
+                Some(exceeded) => println!("run {run} aborted, the {exceeded} budget is exhausted"),
+                None => println!("run {run} aborted"),
+            }
+            break;
+        }
 
-            let mut derivation = derivation.drain().collect::<Vec<_>>();
-            derivation.sort_by_key(|(i, _)| *i);
+        if let Some(path) = &args.checkpoint {
+            save_checkpoint(&args, &context, &axioms, path);
+        }
 
-            for (i, (meta, formula)) in &derivation {
-                println!("{i}: {formula} ({s})", s = meta.sources.iter().join("; "));
+        let num_entries = context.entries.len();
+
+        if let Some(pending) = context.pending() {
+            println!("run {run}, now {num_entries} entries, {pending} pending");
+        } else {
+            println!("run {run}, now {num_entries} entries");
+        }
+        println!("{stats}");
+        print_matched_hints(&context, args.notation);
+
+        if !targets.is_empty() {
+            let missing = find_targets(&context, &mut targets, args.notation);
+            if missing == 0 {
+                break;
             }
+            println!(
+                "{missing} of {} targets not found in iteration {run}",
+                targets.len()
+            );
+        }
 
-            dbg!(&derivation.len());
-            dbg!(&found.sources.len());
+        println!("run {run} complete");
+        if interrupts.stop.load(Ordering::Relaxed) {
+            break;
         }
     }
 
+    let exceeded = context.exceeded_budget();
+    if interrupts.stop.load(Ordering::Relaxed) || exceeded.is_some() {
+        // let the stats below see every entry again
+        interrupts.abort.store(false, Ordering::Relaxed);
+        print_summary(&context, &targets);
+    }
+
+    let hints = context.hints();
+    if !hints.is_empty() {
+        let matched = hints.iter().filter(|h| h.matched.is_some()).count();
+        println!("matched {matched} of {} hints", hints.len());
+    }
+
+    print_target_proofs(&args, &context, &axioms, &targets);
+
     if let Some(path) = args.stats {
-        let mut file = std::fs::File::create(path).unwrap();
+        match exceeded {
+            // the stats would need another iteration
+            Some(exceeded) => println!("not writing stats, the {exceeded} budget is exhausted"),
+            None => write_stats(&context, &path, &interrupts.abort)?,
+        }
+    }
+    Ok(())
+}
 
-        println!("generating stats...");
+/// Flags set by Ctrl-C: the first press stops the run after the current iteration, the
+/// second aborts the iteration and the third exits at once.
+struct Interrupts {
+    stop: Arc<AtomicBool>,
+    abort: Arc<AtomicBool>,
+}
 
-        let stats = context
-            .new_entries_iter(&(|_, _, _| ()))
-            .fold(HashMap::new, |mut acc, val| {
-                acc.entry(val.0.len()).or_insert(0).add_assign(1);
-                acc
-            })
-            .reduce(HashMap::new, |mut a, mut b| {
-                a.par_extend(b.par_drain());
-                a
-            });
+fn watch_interrupts() -> Interrupts {
+    let stop = Arc::new(AtomicBool::new(false));
+    let abort = Arc::new(AtomicBool::new(false));
+    let presses = AtomicUsize::new(0);
+    let (s, a) = (stop.clone(), abort.clone());
+    ctrlc::set_handler(move || match presses.fetch_add(1, Ordering::Relaxed) {
+        0 => {
+            eprintln!(
+                "interrupted, finishing the current iteration; press Ctrl-C again to abort it"
+            );
+            s.store(true, Ordering::Relaxed);
+        }
+        1 => {
+            eprintln!("aborting the current iteration; press Ctrl-C again to exit immediately");
+            a.store(true, Ordering::Relaxed);
+        }
+        _ => std::process::exit(130),
+    })
+    .unwrap_or_else(|e| fail(ErrorKind::Io, format!("cannot handle Ctrl-C: {e}")));
+    Interrupts { stop, abort }
+}
+
+/// Prints how many entries every iteration added and how many targets were found.
+fn print_summary<L: Language>(context: &Context<L>, targets: &[Target<L>]) {
+    println!(
+        "stopped after run {}",
+        context.generation().saturating_sub(1)
+    );
+    let mut per_generation = vec![0; context.generation() + 1];
+    for meta in context.entries.values() {
+        per_generation[meta.generation] += 1;
+    }
+    for (generation, count) in per_generation.iter().enumerate() {
+        println!("generation {generation}: {count} entries");
+    }
+    if !targets.is_empty() {
+        let found = targets.iter().filter(|t| t.found.is_some()).count();
+        println!("found {found} of {} targets", targets.len());
+    }
+}
+
+fn language_name(args: &Args) -> String {
+    args.language
+        .to_possible_value()
+        .unwrap()
+        .get_name()
+        .to_owned()
+}
 
-        writeln!(file, "len,amount")?;
+fn load_checkpoint<L: Language>(args: &Args, axioms: &[Named<L>], path: &str) -> Context<L>
+where
+    L::Variant<()>: Display + TryFrom<char>,
+{
+    let error = |e: &dyn Display| -> ! {
+        fail(
+            ErrorKind::InvalidValue,
+            format!("cannot resume from `{path}`: {e}"),
+        )
+    };
+    let file = std::fs::File::open(path).unwrap_or_else(|e| error(&e));
+    let context = Context::load(&language_name(args), axioms, io::BufReader::new(file))
+        .unwrap_or_else(|e| error(&e));
+    println!(
+        "resuming after run {} with {} entries",
+        context.generation().saturating_sub(1),
+        context.entries.len()
+    );
+    context
+}
+
+/// Writes the checkpoint to a temporary file first, so that an interrupted write
+/// does not destroy the previous checkpoint.
+fn save_checkpoint<L: Language>(args: &Args, context: &Context<L>, axioms: &[Named<L>], path: &str)
+where
+    L::Variant<()>: Display + TryFrom<char>,
+{
+    let temporary = format!("{path}.tmp");
+    let result = std::fs::File::create(&temporary)
+        .map_err(|e| e.to_string())
+        .and_then(|file| {
+            let mut writer = io::BufWriter::new(file);
+            context
+                .save(&language_name(args), axioms, &mut writer)
+                .map_err(|e| e.to_string())?;
+            writer.flush().map_err(|e| e.to_string())
+        })
+        .and_then(|()| std::fs::rename(&temporary, path).map_err(|e| e.to_string()));
+    if let Err(e) = result {
+        fail(
+            ErrorKind::Io,
+            format!("cannot write checkpoint `{path}`: {e}"),
+        );
+    }
+}
+
+/// A formula given with `--search` or `--targets`.
+struct Target<L: Language> {
+    name: Option<String>,
+    formula: Normal<L>,
+    /// The number of iterations after which it was found
+    found: Option<usize>,
+}
+
+impl<L: Infix> Target<L>
+where
+    L::Variant<()>: Display,
+{
+    fn label(&self, notation: Notation) -> String {
+        let formula = notation.show(self.formula.terms());
+        match &self.name {
+            Some(name) => format!("{name} ({formula})"),
+            None => format!("formula ({formula})"),
+        }
+    }
+}
+
+/// Looks for entries that subsume the targets that were not found yet.
+/// Returns the number of targets that are still missing.
+fn find_targets<L: Infix>(
+    context: &Context<L>,
+    targets: &mut [Target<L>],
+    notation: Notation,
+) -> usize
+where
+    L::Variant<()>: Display,
+{
+    let iterations = context.generation();
+    for target in targets.iter_mut().filter(|t| t.found.is_none()) {
+        let f = &target.formula;
+        let Some((g, meta, substitution)) = context.find_subsuming(f) else {
+            continue;
+        };
+        println!(
+            "Found {} after {iterations} iterations",
+            target.label(notation)
+        );
+        if g != f {
+            let substitution = notation.show_substitution(
+                substitution
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(x, s)| Some((x, (*s)?))),
+            );
+            let label = context.label(meta.index);
+            let g = notation.show(g.terms());
+            println!("It is an instance of {label} ({g}) with {substitution}");
+        }
+        target.found = Some(iterations);
+    }
+    targets.iter().filter(|t| t.found.is_none()).count()
+}
+
+/// Prints a proof of the entry `f`, numbering the steps from 1.
+fn print_proof<L: Infix>(context: &Context<L>, proof: &Proof<L>, notation: Notation)
+where
+    L::Variant<()>: Display,
+{
+    for (n, step) in proof.steps.iter().enumerate() {
+        let formula = notation.show(step.formula.terms());
+        match step.source {
+            Source::Axiom => println!(
+                "{}: {formula} (AXIOM {})",
+                n + 1,
+                context.axiom_label(step.index)
+            ),
+            source @ Source::MP(minor, major) => {
+                println!("{}: {formula} ({source})", n + 1);
+                let (minor, major) = (&proof.steps[minor - 1], &proof.steps[major - 1]);
+                let (_, unifier) = modus_ponens_with_unifier(&minor.formula, &major.formula)
+                    .expect("proof steps follow by modus ponens");
+                let show = |substitution: &[Box<[Term<L, ()>]>]| {
+                    notation.show_substitution(substitution.iter().map(|t| &t[..]).enumerate())
+                };
+                println!("    minor: {}", show(&unifier.minor));
+                println!("    major: {}", show(&unifier.major));
+            }
+        }
+    }
+    println!("D-notation: {}", proof.to_dproof());
+}
+
+fn print_target_proofs<L: Infix>(
+    args: &Args,
+    context: &Context<L>,
+    axioms: &[Named<L>],
+    targets: &[Target<L>],
+) where
+    L::Variant<()>: Display,
+{
+    let choice = match args.proofs {
+        Proofs::Smallest => SourceChoice::Smallest,
+        Proofs::First => SourceChoice::First,
+    };
+    let axioms: Vec<_> = axioms.iter().map(|(_, f)| f.clone()).collect();
+    for target in targets {
+        let label = target.label(args.notation);
+        let Some(iterations) = target.found else {
+            println!("{label} not found");
+            continue;
+        };
+        println!("Proof of {label}, found after {iterations} iterations:");
+        // the entry that was found may have been retired by a more general one since
+        let (f, _, _) = context.find_subsuming(&target.formula).unwrap();
+        let proof = context.proof_of(f, choice).unwrap();
+        print_proof(context, &proof, args.notation);
+        if args.check {
+            match check::check(&axioms, &proof) {
+                Ok(()) => println!("The proof was checked independently"),
+                Err(e) => println!("The proof is invalid at {e}"),
+            }
+        }
+        if args.expand {
+            let expanded = HilbertProof::expand(&proof);
+            print_expanded(context, &axioms, &expanded, args.notation);
+        }
+    }
+}
 
-        for (len, amount) in &stats {
-            writeln!(file, "{len},{amount}")?;
+fn print_expanded<L: Infix>(
+    context: &Context<L>,
+    axioms: &[Normal<L>],
+    proof: &HilbertProof<L>,
+    notation: Notation,
+) where
+    L::Variant<()>: Display,
+{
+    println!("Expanded proof:");
+    for (n, line) in proof.lines.iter().enumerate() {
+        let formula = notation.show(&line.formula);
+        match &line.justification {
+            Justification::Axiom {
+                index,
+                substitution,
+            } => {
+                let substitution =
+                    notation.show_substitution(substitution.iter().map(|t| &t[..]).enumerate());
+                let label = context.axiom_label(*index);
+                println!("{}: {formula} (AXIOM {label} with {substitution})", n + 1);
+            }
+            Justification::MP(minor, major) => {
+                println!("{}: {formula} (MP {minor}, {major})", n + 1);
+            }
         }
+    }
+    match proof.verify(axioms) {
+        Ok(()) => println!("Every line of the expanded proof was verified"),
+        Err(line) => println!("Line {line} of the expanded proof is invalid"),
+    }
+}
 
-        println!("size: {}", stats.len());
+/// Prints the hints that were first matched in the last step.
+fn print_matched_hints<L: Infix>(context: &Context<L>, notation: Notation)
+where
+    L::Variant<()>: Display,
+{
+    for hint in context.hints() {
+        if hint.matched == Some(context.generation()) {
+            let name = hint
+                .name
+                .as_deref()
+                .map_or(String::new(), |n| format!("{n}: "));
+            println!(
+                "matched hint {name}{} in step {}",
+                notation.show(hint.formula.terms()),
+                context.generation()
+            );
+        }
     }
+}
+
+/// Writes how many formulas of each length the next iteration would derive. Nothing is
+/// written if `abort` is set or a budget is exceeded before the iteration is complete.
+fn write_stats<L: Language>(
+    context: &Context<L>,
+    path: &str,
+    abort: &AtomicBool,
+) -> io::Result<()> {
+    println!("generating stats...");
+
+    let stats = context
+        .new_entries_iter(&(|_, _, _| ()))
+        .fold(HashMap::new, |mut acc, val| {
+            acc.entry(val.0.len()).or_insert(0).add_assign(1);
+            acc
+        })
+        .reduce(HashMap::new, |mut a, mut b| {
+            a.par_extend(b.par_drain());
+            a
+        });
+    if abort.load(Ordering::Relaxed) {
+        println!("stats aborted, not writing {path}");
+        return Ok(());
+    }
+    // the budget is checked while the stats are generated, so they may be incomplete
+    if let Some(exceeded) = context.exceeded_budget() {
+        println!("stats aborted, the {exceeded} budget is exhausted, not writing {path}");
+        return Ok(());
+    }
+
+    let mut file = std::fs::File::create(path)?;
+    writeln!(file, "len,amount")?;
+
+    for (len, amount) in &stats {
+        writeln!(file, "{len},{amount}")?;
+    }
+
+    println!("size: {}", stats.len());
     Ok(())
 }
+
+#[cfg(test)]
+mod test {
+    use clap::Parser;
+
+    use super::Args;
+
+    #[test]
+    fn stats_need_level_saturation() {
+        assert!(Args::try_parse_from(["ba", "--stats", "stats.csv"]).is_ok());
+        assert!(Args::try_parse_from(["ba", "--given", "--stats", "stats.csv"]).is_err());
+    }
+}
@@ -0,0 +1,115 @@
+use ahash::{HashMap, HashSet};
+
+use crate::{
+    dproof::{DEvalError, DProof, Lemmas},
+    formula::language::{Language, Normal, Variables},
+    input::{self, InputError, InputErrorKind},
+};
+
+/// A proof from a proof collection, checked against the axioms.
+#[derive(Debug)]
+pub struct Imported<L: Language> {
+    pub name: String,
+    /// The theorem the collection claims to prove
+    pub expected: Normal<L>,
+    /// The theorem the proof actually derives and its number of distinct detachments,
+    /// or the step that fails
+    pub result: Result<(Normal<L>, usize), DEvalError<L>>,
+}
+
+impl<L: Language> Imported<L> {
+    pub fn verified(&self) -> bool {
+        matches!(&self.result, Ok((theorem, _)) if *theorem == self.expected)
+    }
+}
+
+/// Reads and checks a collection of condensed detachment proofs in the style of Metamath's
+/// `pmproofs.txt`, with a name, the theorem and its proof in D-notation on every line:
+///
+/// ```text
+/// *2.08  Cpp    DD211
+/// *2.3   CpCqq  D1[*2.08]   # refers to the theorem above
+/// ```
+///
+/// A proof may refer to any earlier theorem by name. If a proof derives a different
+/// theorem than the one given, later proofs refer to the theorem it actually derives.
+pub fn read_proofs<L: Language>(
+    text: &str,
+    axioms: &[Normal<L>],
+    variables: Variables<'_>,
+) -> Result<Vec<Imported<L>>, InputError>
+where
+    L::Variant<()>: TryFrom<char>,
+{
+    let mut lemmas: Lemmas<L> = HashMap::default();
+    let mut imported = Vec::new();
+    for (line, content) in input::lines(text) {
+        let error = |kind| InputError { line, kind };
+        let [name, expected, proof] = content.split_whitespace().collect::<Vec<_>>()[..] else {
+            return Err(error(InputErrorKind::MissingProof));
+        };
+        if imported.iter().any(|i: &Imported<L>| i.name == name) {
+            return Err(error(InputErrorKind::DuplicateName(name.to_owned())));
+        }
+        let expected =
+            Normal::parse_with(expected, variables).map_err(|e| error(InputErrorKind::Parse(e)))?;
+        let proof: DProof = proof
+            .parse()
+            .map_err(|e| error(InputErrorKind::InvalidProof(e)))?;
+
+        let mut steps = HashSet::default();
+        let result = proof.evaluate_with(axioms, &lemmas, &mut steps);
+        if let Ok(theorem) = &result {
+            lemmas.insert(name.to_owned(), (theorem.clone(), steps.clone()));
+        }
+        imported.push(Imported {
+            name: name.to_owned(),
+            expected,
+            result: result.map(|theorem| (theorem, steps.len())),
+        });
+    }
+    Ok(imported)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        dproof::DEvalError,
+        formula::{langs::ImpNeg, language::Variables},
+    };
+
+    use super::{read_proofs, Imported};
+
+    #[test]
+    fn verifies_proofs() {
+        let text = "
+            # from Łukasiewicz's third system
+            id     Cpp    DD211
+            id2    CpCqq  D1[id]
+            wrong  Cpq    DD211
+            fail   Cpp    D31
+            later  Cpp    [fail]
+        ";
+        let imported =
+            read_proofs::<ImpNeg>(text, &ImpNeg::lukasiewicz3(), Variables::Any).unwrap();
+        let verified: Vec<_> = imported.iter().map(Imported::verified).collect();
+        assert_eq!(verified, [true, true, false, false, false]);
+        assert_eq!(imported[0].result.as_ref().unwrap().1, 2);
+        assert_eq!(imported[1].result.as_ref().unwrap().1, 3);
+        assert!(imported[2].result.is_ok());
+        assert!(matches!(
+            imported[3].result,
+            Err(DEvalError::NotDetachable { position: 0, .. })
+        ));
+        assert!(matches!(
+            imported[4].result,
+            Err(DEvalError::UnknownLemma { .. })
+        ));
+
+        let read = |text| read_proofs::<ImpNeg>(text, &[], Variables::Only("pq"));
+        assert!(read("id Cpp").is_err());
+        assert!(read("id Cpp DD21").is_err());
+        assert!(read("id Cpp 1\nid Cpp 1").is_err());
+        assert!(read("id Cpr 1").is_err());
+    }
+}
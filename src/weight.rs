@@ -0,0 +1,149 @@
+use std::collections::HashMap;
+
+use crate::{
+    formula::{
+        index::subterm_len,
+        language::{matching, Language, Normal, Term, Variables},
+    },
+    input::{self, InputError, InputErrorKind},
+};
+
+/// Assigns a weight to formulas, used to rank and bound the formulas of a search.
+///
+/// Every connective and variable contributes its weight plus `depth` for each connective
+/// it is nested in. A subterm that is an instance of a template instead weighs as much as
+/// the template plus the subterms its variables are replaced with. With the default
+/// weights, the weight of a formula is its length.
+///
+/// Formulas that match a hint get the weight `hint` instead, see [`Context::set_hints`].
+///
+/// [`Context::set_hints`]: crate::context::Context::set_hints
+#[derive(Debug)]
+pub struct Weights<L: Language> {
+    symbols: HashMap<L::Variant<()>, usize>,
+    var: usize,
+    depth: usize,
+    templates: Vec<(Normal<L>, usize)>,
+    pub hint: usize,
+}
+
+impl<L: Language> Default for Weights<L> {
+    fn default() -> Self {
+        Self {
+            symbols: HashMap::new(),
+            var: 1,
+            depth: 0,
+            templates: Vec::new(),
+            hint: 0,
+        }
+    }
+}
+
+impl<L: Language> Weights<L> {
+    pub fn weight(&self, f: &Normal<L>) -> usize {
+        self.subterm(f.terms(), 0)
+    }
+
+    /// The weight of the subterm at the start of `terms`, which is nested in `depth` connectives.
+    fn subterm(&self, terms: &[Term<L, ()>], depth: usize) -> usize {
+        for (template, weight) in &self.templates {
+            if let Some(substitution) = matching(template.terms(), terms) {
+                return weight
+                    + substitution
+                        .into_iter()
+                        .flatten()
+                        .map(|s| self.subterm(s, depth))
+                        .sum::<usize>();
+            }
+        }
+        match &terms[0] {
+            Term::Var(_) => self.var + depth * self.depth,
+            Term::Term(v) => {
+                let own = self.symbols.get(v).copied().unwrap_or(1) + depth * self.depth;
+                let mut rest = &terms[1..];
+                let mut children = 0;
+                for () in L::children(v) {
+                    children += self.subterm(rest, depth + 1);
+                    rest = &rest[subterm_len(rest)..];
+                }
+                own + children
+            }
+        }
+    }
+
+    /// Reads weights from a file with one setting per line:
+    ///
+    /// ```text
+    /// symbol N 2       # weight of a connective, 1 by default
+    /// var 1            # weight of a variable, 1 by default
+    /// depth 1          # added for each connective a symbol is nested in, 0 by default
+    /// template NNp 20  # weight of any subterm matching NNp, plus the weight of p
+    /// hint 0           # weight of formulas that match a hint, 0 by default
+    /// ```
+    pub fn read(text: &str, variables: Variables<'_>) -> Result<Self, InputError>
+    where
+        L::Variant<()>: TryFrom<char>,
+    {
+        let mut weights = Self::default();
+        for (line, content) in input::lines(text) {
+            let error = |kind| InputError { line, kind };
+            let number = |n: &str| {
+                n.parse::<usize>()
+                    .map_err(|_| error(InputErrorKind::InvalidNumber(n.to_owned())))
+            };
+            match content.split_whitespace().collect::<Vec<_>>()[..] {
+                ["symbol", symbol, weight] => {
+                    let mut chars = symbol.chars();
+                    let variant = match (chars.next(), chars.next()) {
+                        (Some(c), None) => L::Variant::<()>::try_from(c).ok(),
+                        _ => None,
+                    }
+                    .ok_or_else(|| error(InputErrorKind::UnknownSymbol(symbol.to_owned())))?;
+                    weights.symbols.insert(variant, number(weight)?);
+                }
+                ["var", weight] => weights.var = number(weight)?,
+                ["depth", weight] => weights.depth = number(weight)?,
+                ["hint", weight] => weights.hint = number(weight)?,
+                ["template", formula, weight] => {
+                    let formula = Normal::parse_with(formula, variables)
+                        .map_err(|e| error(InputErrorKind::Parse(e)))?;
+                    weights.templates.push((formula, number(weight)?));
+                }
+                _ => {
+                    return Err(error(InputErrorKind::UnknownSetting(content.to_owned())));
+                }
+            }
+        }
+        Ok(weights)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::formula::{
+        langs::ImpNeg,
+        language::{Normal, Variables},
+    };
+
+    use super::Weights;
+
+    #[test]
+    fn weights() {
+        let f: Normal<ImpNeg> = "CNNpCqNNNp".parse().unwrap();
+        assert_eq!(Weights::default().weight(&f), f.len());
+
+        let weights = Weights::read("symbol N 2\nvar 3\n", Variables::Any).unwrap();
+        assert_eq!(weights.weight(&f), 2 + 5 * 2 + 3 * 3);
+
+        let weights = Weights::read("depth 1", Variables::Any).unwrap();
+        let g: Normal<ImpNeg> = "CpNq".parse().unwrap();
+        assert_eq!(weights.weight(&g), 1 + 2 + 2 + 3);
+
+        // the template matches twice, once with `p := p` and once with `p := Np`
+        let weights = Weights::read("template NNp 20", Variables::Only("p")).unwrap();
+        assert_eq!(weights.weight(&f), 3 + 20 + 1 + 20 + 2);
+
+        assert!(Weights::<ImpNeg>::read("symbol X 2", Variables::Any).is_err());
+        assert!(Weights::<ImpNeg>::read("var two", Variables::Any).is_err());
+    }
+}